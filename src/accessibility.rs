@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{game_state::GameState, settings::Settings, sim::Sim, spawning::CircleButton};
+
+/// Shape cues for state otherwise only shown by color, and the palette swaps
+/// picked in the settings.
+pub struct AccessibilityPlugin;
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (MismatchGlyph::update, draw_check_mark, Background::tint)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Colors for state that would otherwise be told apart by hue alone.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Red-weak vision.
+    Protanopia,
+    /// Green-weak vision.
+    Deuteranopia,
+    /// Blue-weak vision.
+    Tritanopia,
+    /// Bright warnings over darkened art.
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Standard,
+        Palette::Protanopia,
+        Palette::Deuteranopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    /// Key of the palette's name.
+    pub fn label(self) -> &'static str {
+        match self {
+            Palette::Standard => "palette-standard",
+            Palette::Protanopia => "palette-protan",
+            Palette::Deuteranopia => "palette-deutan",
+            Palette::Tritanopia => "palette-tritan",
+            Palette::HighContrast => "palette-contrast",
+        }
+    }
+
+    /// The palette after this one, wrapping around.
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// The palette before this one, wrapping around.
+    pub fn previous(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Category box of a stack holding items of another category, and the
+    /// clock hand while the day's buy is still due.
+    pub fn warning(self) -> Color {
+        match self {
+            Palette::Standard => Color::CRIMSON,
+            Palette::Protanopia => Color::rgb_u8(240, 200, 0),
+            Palette::Deuteranopia => Color::rgb_u8(230, 159, 0),
+            Palette::Tritanopia => Color::rgb_u8(213, 40, 90),
+            Palette::HighContrast => Color::rgb_u8(255, 230, 0),
+        }
+    }
+
+    /// Clock hand once the day's buy is done.
+    pub fn done(self) -> Color {
+        match self {
+            Palette::Tritanopia => Color::rgb_u8(150, 235, 235),
+            _ => Color::WHITE,
+        }
+    }
+
+    /// Popups for stress going down.
+    pub fn relief(self) -> Color {
+        match self {
+            Palette::Standard => Color::DARK_GREEN,
+            Palette::Protanopia | Palette::Deuteranopia => Color::rgb_u8(0, 114, 178),
+            Palette::Tritanopia => Color::rgb_u8(0, 140, 140),
+            Palette::HighContrast => Color::rgb_u8(0, 255, 255),
+        }
+    }
+
+    /// Popups for stress going up.
+    pub fn stress(self) -> Color {
+        match self {
+            Palette::Standard => Color::CRIMSON,
+            Palette::Protanopia | Palette::Deuteranopia => Color::rgb_u8(213, 94, 0),
+            Palette::Tritanopia => Color::rgb_u8(213, 40, 90),
+            Palette::HighContrast => Color::rgb_u8(255, 230, 0),
+        }
+    }
+
+    /// Tint over the background art, darker in high contrast so items and
+    /// warnings stand out.
+    pub fn background(self) -> Color {
+        match self {
+            Palette::HighContrast => Color::rgb(0.35, 0.35, 0.35),
+            _ => Color::WHITE,
+        }
+    }
+}
+
+/// Warning sign next to the label of a stack holding items of another category.
+#[derive(Component)]
+pub struct MismatchGlyph {
+    pub stack: usize,
+}
+
+impl MismatchGlyph {
+    fn update(
+        sim: Res<Sim>,
+        settings: Res<Settings>,
+        mut glyphs: Query<(&MismatchGlyph, &mut Visibility, &mut Text)>,
+    ) {
+        for (glyph, mut visibility, mut text) in &mut glyphs {
+            let mismatched = sim
+                .stacks
+                .get(glyph.stack)
+                .is_some_and(|stack| stack.mismatched() > 0);
+            let new_visibility = if settings.shape_cues && mismatched {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            };
+            if *visibility != new_visibility {
+                *visibility = new_visibility;
+            }
+            let color = settings.palette.warning();
+            if text.sections[0].style.color != color {
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
+
+/// Check mark on the buy clock once the day's buy is done.
+fn draw_check_mark(
+    sim: Res<Sim>,
+    settings: Res<Settings>,
+    button: Query<&Transform, With<CircleButton>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok(button) = button.get_single() else {
+        return;
+    };
+    if !settings.shape_cues || !sim.today.clicked_today {
+        return;
+    }
+    let corner = button.translation.xy() + Vec2::new(14., -14.);
+    gizmos.linestrip_2d(
+        [
+            corner + Vec2::new(-5., 1.),
+            corner + Vec2::new(-1., -3.),
+            corner + Vec2::new(6., 5.),
+        ],
+        settings.palette.done(),
+    );
+}
+
+/// The art behind the play area.
+#[derive(Component)]
+pub struct Background;
+
+impl Background {
+    fn tint(settings: Res<Settings>, mut q: Query<&mut Sprite, With<Background>>) {
+        let color = settings.palette.background();
+        for mut sprite in &mut q {
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
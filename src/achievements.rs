@@ -0,0 +1,440 @@
+use bevy::{prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::{despawn, menu_root, spawn_button},
+    catalog::ItemCatalog,
+    game_state::GameState,
+    layers,
+    locale::Locale,
+    replay::Replay,
+    sim::{Sim, SimEvent, Variant},
+    storage,
+};
+
+/// Unlocks achievements as the sim reports what happens in a run, toasts them
+/// next to the consumed counter and lists them on a gallery screen. Unlocks
+/// and lifetime counts are saved between sessions.
+pub struct AchievementsPlugin;
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            storage::load::<AchievementProgress>(AchievementProgress::KEY).unwrap_or_default(),
+        )
+        .init_resource::<RunTracker>()
+        .add_event::<AchievementUnlocked>()
+        .add_systems(OnEnter(GameState::Playing), RunTracker::reset)
+        .add_systems(
+            OnExit(GameState::Playing),
+            AchievementProgress::save.run_if(not(resource_exists::<Replay>())),
+        )
+        .add_systems(
+            OnTransition {
+                from: GameState::Playing,
+                to: GameState::Failed,
+            },
+            AchievementProgress::finish_run.run_if(not(resource_exists::<Replay>())),
+        )
+        .add_systems(
+            OnTransition {
+                from: GameState::Playing,
+                to: GameState::Victory,
+            },
+            AchievementProgress::finish_run.run_if(not(resource_exists::<Replay>())),
+        )
+        .add_systems(Update, (Toast::spawn, Toast::animate).chain())
+        .add_systems(OnEnter(GameState::Achievements), spawn_gallery)
+        .add_systems(OnExit(GameState::Achievements), despawn::<GalleryMarker>)
+        .add_systems(
+            Update,
+            close_gallery.run_if(in_state(GameState::Achievements)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Achievement {
+    /// Consume [`Achievement::CONSUME_TOTAL`] items over every run.
+    ConsumeTotal,
+    /// End a run of at least a week without any item on the wrong stack when
+    /// a day ends.
+    TidyRun,
+    /// Get through the day of a bulk purchase of [`Achievement::BULK_SIZE`] items.
+    BigHaul,
+    /// Consume every book listed in the catalog, over every run.
+    EveryBook,
+    /// Buy something every day for [`Achievement::BUY_STREAK`] days in a row.
+    DailyBuyer,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::ConsumeTotal,
+        Achievement::TidyRun,
+        Achievement::BigHaul,
+        Achievement::EveryBook,
+        Achievement::DailyBuyer,
+    ];
+    const CONSUME_TOTAL: u32 = 100;
+    const BULK_SIZE: usize = 8;
+    const BUY_STREAK: usize = 10;
+    const TIDY_DAYS: usize = 7;
+    const BOOKS: &'static str = "books";
+
+    /// What the unlock is saved under, and the start of its string keys.
+    pub fn id(self) -> &'static str {
+        match self {
+            Achievement::ConsumeTotal => "consume-total",
+            Achievement::TidyRun => "tidy-run",
+            Achievement::BigHaul => "big-haul",
+            Achievement::EveryBook => "every-book",
+            Achievement::DailyBuyer => "daily-buyer",
+        }
+    }
+
+    pub fn name(self, locale: &Locale) -> String {
+        locale.get(&format!("achievement-{}-name", self.id()))
+    }
+
+    pub fn description(self, locale: &Locale) -> String {
+        locale.get(&format!("achievement-{}-description", self.id()))
+    }
+}
+
+/// Sent once when an achievement is unlocked.
+#[derive(Event, Clone, Copy)]
+pub struct AchievementUnlocked(pub Achievement);
+
+/// Unlocked achievements and what counts towards them across runs.
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AchievementProgress {
+    /// Ids of the unlocked achievements.
+    pub unlocked: Vec<String>,
+    /// Items consumed over every run.
+    pub consumed: u32,
+    /// Catalog indices of the books consumed over every run.
+    pub books: Vec<usize>,
+}
+
+/// What counts towards achievements in the current run.
+#[derive(Resource, Default)]
+pub struct RunTracker {
+    /// Days in a row something was bought.
+    buy_streak: usize,
+    /// An item was on the wrong stack at the end of a day.
+    untidy: bool,
+    days: usize,
+    /// A big enough bulk purchase happened today.
+    big_haul: bool,
+}
+
+impl RunTracker {
+    fn reset(mut tracker: ResMut<RunTracker>) {
+        *tracker = RunTracker::default();
+    }
+}
+
+impl AchievementProgress {
+    const KEY: &'static str = "achievements";
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.iter().any(|id| id == achievement.id())
+    }
+
+    /// `(done, needed)` for achievements that count up across runs.
+    pub fn count(&self, achievement: Achievement, catalog: &ItemCatalog) -> Option<(u32, u32)> {
+        match achievement {
+            Achievement::ConsumeTotal => Some((
+                self.consumed.min(Achievement::CONSUME_TOTAL),
+                Achievement::CONSUME_TOTAL,
+            )),
+            Achievement::EveryBook => {
+                let books = catalog
+                    .categories
+                    .iter()
+                    .find(|category| category.id == Achievement::BOOKS)?;
+                Some((self.books.len() as u32, books.items.len() as u32))
+            }
+            _ => None,
+        }
+    }
+
+    /// Lifetime counts are saved once the run is over, unlocks right away.
+    fn save(progress: Res<AchievementProgress>) {
+        storage::save(Self::KEY, &*progress);
+    }
+
+    /// Returns whether the achievement wasn't unlocked before.
+    fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.is_unlocked(achievement) {
+            return false;
+        }
+        self.unlocked.push(achievement.id().to_string());
+        true
+    }
+
+    pub fn track(
+        mut events: EventReader<SimEvent>,
+        mut progress: ResMut<AchievementProgress>,
+        mut tracker: ResMut<RunTracker>,
+        mut unlocked: EventWriter<AchievementUnlocked>,
+        sim: Res<Sim>,
+        catalog: Res<ItemCatalog>,
+    ) {
+        let mut newly = Vec::new();
+        for event in events.read() {
+            match *event {
+                SimEvent::Consumed {
+                    item_type, variant, ..
+                } => {
+                    progress.consumed += 1;
+                    if progress.consumed >= Achievement::CONSUME_TOTAL {
+                        newly.push(Achievement::ConsumeTotal);
+                    }
+                    let category = catalog.category(item_type);
+                    if let Variant::Catalog(index) = variant {
+                        if category.id == Achievement::BOOKS && !progress.books.contains(&index) {
+                            progress.books.push(index);
+                            if progress.books.len() >= category.items.len() {
+                                newly.push(Achievement::EveryBook);
+                            }
+                        }
+                    }
+                }
+                SimEvent::BulkPurchase { size } => {
+                    tracker.big_haul |= size >= Achievement::BULK_SIZE;
+                }
+                SimEvent::DayEnded { bought, mismatched } => {
+                    tracker.days += 1;
+                    tracker.untidy |= mismatched > 0;
+                    tracker.buy_streak = if bought { tracker.buy_streak + 1 } else { 0 };
+                    if tracker.buy_streak >= Achievement::BUY_STREAK {
+                        newly.push(Achievement::DailyBuyer);
+                    }
+                    // the run made it through the day of the purchase
+                    if std::mem::take(&mut tracker.big_haul) && !sim.failed() {
+                        newly.push(Achievement::BigHaul);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut changed = false;
+        for achievement in newly {
+            if progress.unlock(achievement) {
+                unlocked.send(AchievementUnlocked(achievement));
+                changed = true;
+            }
+        }
+        if changed {
+            storage::save(Self::KEY, &*progress);
+        }
+    }
+
+    fn finish_run(
+        mut progress: ResMut<AchievementProgress>,
+        tracker: Res<RunTracker>,
+        mut unlocked: EventWriter<AchievementUnlocked>,
+    ) {
+        if tracker.untidy || tracker.days < Achievement::TIDY_DAYS {
+            return;
+        }
+        if progress.unlock(Achievement::TidyRun) {
+            unlocked.send(AchievementUnlocked(Achievement::TidyRun));
+            storage::save(Self::KEY, &*progress);
+        }
+    }
+}
+
+/// Unlock notice next to the consumed counter. Later ones line up below.
+#[derive(Component)]
+struct Toast {
+    timer: Timer,
+}
+
+impl Toast {
+    const SIZE: Vec2 = Vec2::new(200., 18.);
+    const FADE_SECS: f32 = 0.5;
+
+    fn spawn(
+        mut commands: Commands,
+        mut events: EventReader<AchievementUnlocked>,
+        locale: Res<Locale>,
+        toasts: Query<&Toast>,
+    ) {
+        let shown = toasts.iter().count();
+        for (place, AchievementUnlocked(achievement)) in (shown..).zip(events.read()) {
+            let y = 160. - place as f32 * (Self::SIZE.y + 4.);
+            commands
+                .spawn((
+                    Toast {
+                        timer: Timer::from_seconds(4., TimerMode::Once),
+                    },
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba_u8(102, 45, 60, 230),
+                            custom_size: Some(Self::SIZE),
+                            anchor: Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(-245., y, layers::UI + 40.),
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.spawn(Text2dBundle {
+                        text: Text::from_section(
+                            locale.format(
+                                "achievement-unlocked",
+                                &[("name", &achievement.name(&locale))],
+                            ),
+                            TextStyle {
+                                font: locale.font.clone(),
+                                font_size: 10.,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        text_anchor: Anchor::CenterLeft,
+                        transform: Transform::from_xyz(4., 0., 1.),
+                        ..default()
+                    });
+                });
+        }
+    }
+
+    /// Fades toasts out at the end of their time.
+    fn animate(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut toasts: Query<(Entity, &mut Toast, &mut Sprite, &Children)>,
+        mut texts: Query<&mut Text>,
+    ) {
+        for (e, mut toast, mut sprite, children) in &mut toasts {
+            if toast.timer.tick(time.delta()).finished() {
+                commands.entity(e).despawn_recursive();
+                continue;
+            }
+            let alpha = (toast.timer.remaining_secs() / Self::FADE_SECS).min(1.);
+            sprite.color.set_a(alpha * 0.9);
+            for child in children {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    text.sections[0].style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct GalleryMarker;
+
+/// Goes back to the title screen from the gallery.
+#[derive(Component)]
+struct GalleryBackButton;
+
+fn close_gallery(
+    q: Query<&Interaction, (Changed<Interaction>, With<GalleryBackButton>)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    button_inputs: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let clicked = q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    let pressed = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepads.iter().any(|gamepad| {
+            button_inputs.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East))
+        });
+    if clicked || pressed {
+        state.set(GameState::StartScreen);
+    }
+}
+
+fn spawn_gallery(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    progress: Res<AchievementProgress>,
+    catalog: Res<ItemCatalog>,
+) {
+    let font = locale.font.clone();
+    commands
+        .spawn((GalleryMarker, menu_root()))
+        .with_children(|children| {
+            children.spawn(TextBundle::from_section(
+                locale.get("achievements-title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 24.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+
+            for achievement in Achievement::ALL {
+                let unlocked = progress.is_unlocked(achievement);
+                let color = if unlocked {
+                    Color::rgb(0.9, 0.9, 0.9)
+                } else {
+                    Color::rgb(0.5, 0.5, 0.5)
+                };
+                let mut description = achievement.description(&locale);
+                if let (false, Some((done, needed))) =
+                    (unlocked, progress.count(achievement, &catalog))
+                {
+                    description.push_str(&format!("  {done}/{needed}"));
+                }
+                let name = if unlocked {
+                    achievement.name(&locale)
+                } else {
+                    locale.format(
+                        "achievements-locked",
+                        &[("name", &achievement.name(&locale))],
+                    )
+                };
+
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(320.),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(4.)),
+                            ..default()
+                        },
+                        background_color: if unlocked {
+                            Color::DARK_GRAY.into()
+                        } else {
+                            Color::DARK_GRAY.with_a(0.4).into()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            name,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 16.,
+                                color,
+                            },
+                        ));
+                        parent.spawn(TextBundle::from_section(
+                            description,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 10.,
+                                color,
+                            },
+                        ));
+                    });
+            }
+
+            spawn_button(
+                children,
+                GalleryBackButton,
+                &locale.get("achievements-back"),
+                font.clone(),
+            );
+        });
+}
@@ -0,0 +1,292 @@
+//! Scripted players that run endless games headlessly to check the balance.
+//!
+//! `--bot <games>` plays that many seeded games with every [`Strategy`], or
+//! only the one named with `--strategy <name>`, and prints how long they lasted
+//! and how much they consumed instead of opening the window. The seeds count up
+//! from `--seed`, or 0, and game `n` starts from the same stacks as a run with
+//! seed `n`. The balance is the saved difficulty, see [`Difficulty`], with the
+//! presets in `assets/`.
+//! `--bot-out <path>` also writes every game as a CSV line.
+
+use std::{fmt::Write as _, time::Duration};
+
+use bevy_rand::prelude::*;
+use rand_core::SeedableRng;
+
+use crate::{
+    catalog::{self, CATALOG_PATH},
+    curve::{DifficultyCurve, CURVE_PATH},
+    difficulty::Difficulty,
+    high_scores::format_time,
+    launch,
+    presets::{Presets, PRESETS_PATH},
+    seed::seed_bytes,
+    sim::{CategoryRules, ItemId, Sim, SimInput},
+};
+
+/// Length of a bot step. Bots react a little slower than players.
+const STEP: Duration = Duration::from_millis(50);
+/// Games still going after this long are stopped and counted as capped.
+const MAX_TIME: Duration = Duration::from_secs(30 * 60);
+
+/// How a bot plays.
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+    /// Pushes what to do before the next step.
+    fn play(&mut self, sim: &Sim, inputs: &mut Vec<SimInput>);
+}
+
+/// Never touches anything. How long a run lasts on its own.
+pub struct Idle;
+impl Strategy for Idle {
+    fn name(&self) -> &'static str {
+        "idle"
+    }
+
+    fn play(&mut self, _sim: &Sim, _inputs: &mut Vec<SimInput>) {}
+}
+
+/// Buys every day and keeps the queue full, but leaves items where they land.
+pub struct Messy;
+impl Strategy for Messy {
+    fn name(&self) -> &'static str {
+        "messy"
+    }
+
+    fn play(&mut self, sim: &Sim, inputs: &mut Vec<SimInput>) {
+        buy_daily(sim, inputs);
+        fill_queue(sim, inputs);
+    }
+}
+
+/// Buys every day, keeps the queue full and moves items to their own stack.
+pub struct Tidy;
+impl Strategy for Tidy {
+    fn name(&self) -> &'static str {
+        "tidy"
+    }
+
+    fn play(&mut self, sim: &Sim, inputs: &mut Vec<SimInput>) {
+        buy_daily(sim, inputs);
+        sort_stacks(sim, inputs);
+        fill_queue(sim, inputs);
+    }
+}
+
+fn buy_daily(sim: &Sim, inputs: &mut Vec<SimInput>) {
+    if !sim.today.clicked_today {
+        inputs.push(SimInput::Buy);
+    }
+}
+
+/// Queues the stacked item that's quickest to consume, the ones on the wrong
+/// stack first when it's a tie.
+fn fill_queue(sim: &Sim, inputs: &mut Vec<SimInput>) {
+    if sim.queue.len() >= sim.balance.max_queue_items {
+        return;
+    }
+    let quickest = sim
+        .stacks
+        .iter()
+        .flat_map(|stack| {
+            stack
+                .items
+                .iter()
+                .map(move |item| (item, item.item_type == stack.item_type))
+        })
+        .min_by_key(|(item, in_place)| (item.consume_time, *in_place));
+    if let Some((item, _)) = quickest {
+        move_item(item.id, SimInput::AddToQueue(item.id), inputs);
+    }
+}
+
+/// Moves one item on the wrong stack to its own, if that one has room.
+fn sort_stacks(sim: &Sim, inputs: &mut Vec<SimInput>) {
+    for stack in &sim.stacks {
+        for item in &stack.items {
+            if item.item_type == stack.item_type {
+                continue;
+            }
+            let home = sim.stacks.iter().position(|s| {
+                s.item_type == item.item_type && s.current_height() < sim.balance.max_stack_height
+            });
+            if let Some(home) = home {
+                let drop = SimInput::AddToStack {
+                    item: item.id,
+                    stack: home,
+                };
+                move_item(item.id, drop, inputs);
+                return;
+            }
+        }
+    }
+}
+
+/// Picks an item up and drops it, the way a drag does.
+fn move_item(item: ItemId, drop: SimInput, inputs: &mut Vec<SimInput>) {
+    inputs.extend([
+        SimInput::RemoveFromStack(item),
+        drop,
+        SimInput::Release(item),
+    ]);
+}
+
+struct Game {
+    seed: u64,
+    survived: Duration,
+    consumed: u32,
+    days: usize,
+    capped: bool,
+}
+
+/// Everything a game needs besides the seed.
+struct Rules {
+    categories: Vec<CategoryRules>,
+    curve: DifficultyCurve,
+    difficulty: Difficulty,
+    presets: Presets,
+}
+
+impl Rules {
+    fn read() -> Result<Rules, String> {
+        let read = |path: &str| {
+            std::fs::read(format!("assets/{path}")).map_err(|e| format!("{path}: {e}"))
+        };
+        let categories = catalog::read_rules(&read(CATALOG_PATH)?)
+            .map_err(|e| format!("{CATALOG_PATH}: {e}"))?;
+        let curve =
+            DifficultyCurve::parse(&read(CURVE_PATH)?).map_err(|e| format!("{CURVE_PATH}: {e}"))?;
+        let presets =
+            Presets::parse(&read(PRESETS_PATH)?).map_err(|e| format!("{PRESETS_PATH}: {e}"))?;
+        Ok(Rules {
+            categories,
+            curve,
+            difficulty: Difficulty::load(),
+            presets,
+        })
+    }
+
+    fn play(&self, strategy: &mut dyn Strategy, seed: u64) -> Game {
+        let mut rng = GlobalEntropy::<ChaCha8Rng>::from_seed(seed_bytes(seed));
+        let mut sim = Sim::new(
+            self.categories.clone(),
+            self.difficulty.balance(&self.presets),
+            self.curve.clone(),
+            None,
+            &mut rng,
+        );
+        let mut inputs = Vec::new();
+        while !sim.failed() && sim.elapsed < MAX_TIME {
+            inputs.clear();
+            strategy.play(&sim, &mut inputs);
+            sim.step(STEP, &inputs, &mut rng);
+        }
+        Game {
+            seed,
+            survived: sim.elapsed,
+            consumed: sim.consumed.total,
+            days: sim.calendar.day(),
+            capped: !sim.failed(),
+        }
+    }
+}
+
+/// Plays the games asked for on the command line and prints the results.
+pub fn run(games: &str) {
+    let Ok(games) = games.parse::<u64>() else {
+        eprintln!("--bot takes a number of games, not {games:?}");
+        return;
+    };
+    let rules = match Rules::read() {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("could not read the rules: {e}");
+            return;
+        }
+    };
+    let first_seed = match launch::option("seed").map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            eprintln!("--seed should be a whole number");
+            return;
+        }
+        None => 0,
+    };
+    let mut strategies: Vec<Box<dyn Strategy>> =
+        vec![Box::new(Idle), Box::new(Messy), Box::new(Tidy)];
+    if let Some(name) = launch::option("strategy") {
+        strategies.retain(|strategy| strategy.name() == name);
+        if strategies.is_empty() {
+            eprintln!("there is no strategy called {name}, try idle, messy or tidy");
+            return;
+        }
+    }
+
+    println!(
+        "{games} games per strategy on {:?} difficulty, seeds {first_seed} to {}",
+        rules.difficulty.preset,
+        first_seed + games.saturating_sub(1),
+    );
+    let mut csv = String::from("strategy,seed,survived_secs,consumed,days,capped\n");
+    for strategy in &mut strategies {
+        let results: Vec<Game> = (first_seed..first_seed + games)
+            .map(|seed| rules.play(strategy.as_mut(), seed))
+            .collect();
+        for game in &results {
+            let _ = writeln!(
+                csv,
+                "{},{},{:.2},{},{},{}",
+                strategy.name(),
+                game.seed,
+                game.survived.as_secs_f32(),
+                game.consumed,
+                game.days,
+                game.capped,
+            );
+        }
+        report(strategy.name(), &results);
+    }
+
+    if let Some(path) = launch::option("bot-out") {
+        match std::fs::write(&path, csv) {
+            Ok(()) => println!("wrote every game to {path}"),
+            Err(e) => eprintln!("could not write {path}: {e}"),
+        }
+    }
+}
+
+fn report(name: &str, games: &[Game]) {
+    if games.is_empty() {
+        return;
+    }
+    let survived = spread(games.iter().map(|game| game.survived), format_time);
+    let consumed = spread(games.iter().map(|game| game.consumed), |n| n.to_string());
+    let days = spread(games.iter().map(|game| game.days), |n| n.to_string());
+    let capped = games.iter().filter(|game| game.capped).count();
+    println!("{name}");
+    println!("  survived  {survived}");
+    println!("  consumed  {consumed}");
+    println!("  days      {days}");
+    if capped > 0 {
+        println!(
+            "  {capped} of {} games were still going after {}",
+            games.len(),
+            format_time(MAX_TIME),
+        );
+    }
+}
+
+/// Minimum, 10th percentile, median, 90th percentile and maximum.
+fn spread<T: Ord + Copy>(values: impl Iterator<Item = T>, show: impl Fn(T) -> String) -> String {
+    let mut values: Vec<T> = values.collect();
+    values.sort();
+    let at = |p: f32| show(values[((values.len() - 1) as f32 * p).round() as usize]);
+    format!(
+        "min {}  p10 {}  median {}  p90 {}  max {}",
+        at(0.),
+        at(0.1),
+        at(0.5),
+        at(0.9),
+        at(1.),
+    )
+}
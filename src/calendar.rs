@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_state::{GameState, PauseState},
+    layers,
+    locale::Locale,
+    replay::Replay,
+    sim::{Calendar, DayStats, Sim, SimEvent},
+};
+
+/// Shows the day and week of the run and sums up each week when it ends.
+pub struct CalendarPlugin;
+impl Plugin for CalendarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), DayLabel::spawn)
+            .add_systems(OnEnter(PauseState::WeekSummary), WeekSummary::spawn)
+            .add_systems(OnExit(PauseState::WeekSummary), WeekSummary::despawn)
+            .add_systems(
+                Update,
+                WeekSummary::close.run_if(in_state(PauseState::WeekSummary)),
+            );
+    }
+}
+
+/// Short names of the days of the week, Monday first.
+fn weekdays(locale: &Locale) -> impl Iterator<Item = &str> {
+    let names = locale.list("weekdays");
+    (0..Calendar::DAYS_PER_WEEK).map(|i| names.get(i).map_or("", String::as_str))
+}
+
+/// Week and day of the week under the buy button.
+#[derive(Component)]
+pub struct DayLabel;
+
+impl DayLabel {
+    fn spawn(mut commands: Commands, locale: Res<Locale>) {
+        commands.spawn((
+            DayLabel,
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(290., 122., layers::UI + 10.),
+                ..default()
+            },
+        ));
+    }
+
+    pub fn update(sim: Res<Sim>, locale: Res<Locale>, mut q: Query<&mut Text, With<DayLabel>>) {
+        let calendar = &sim.calendar;
+        let weekday = weekdays(&locale).nth(calendar.weekday()).unwrap_or("");
+        let mut label = locale.format(
+            "calendar-day",
+            &[("week", &(calendar.week() + 1)), ("weekday", &weekday)],
+        );
+        if calendar.is_weekend() {
+            label.push('\n');
+            label.push_str(&locale.get("calendar-weekend"));
+        }
+        for mut text in &mut q {
+            if text.sections[0].value != label {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+/// Card listing each day of the week that just ended. Play waits until it's
+/// dismissed.
+#[derive(Component)]
+pub struct WeekSummary;
+
+impl WeekSummary {
+    pub fn open(
+        mut events: EventReader<SimEvent>,
+        sim: Res<Sim>,
+        replay: Option<Res<Replay>>,
+        mut pause: ResMut<NextState<PauseState>>,
+    ) {
+        let week_ended = events
+            .read()
+            .any(|event| matches!(event, SimEvent::WeekEnded));
+        // replays play through without stopping
+        if week_ended && !sim.failed() && replay.is_none() {
+            pause.set(PauseState::WeekSummary);
+        }
+    }
+
+    fn spawn(mut commands: Commands, locale: Res<Locale>, sim: Res<Sim>) {
+        let font = locale.font.clone();
+        let calendar = &sim.calendar;
+        // nothing happens while the card is up, so the last week is the one that ended
+        let week = calendar.week().saturating_sub(1);
+
+        let mut days = String::new();
+        for (name, day) in weekdays(&locale).zip(calendar.week_days(week)) {
+            days.push_str(&format!("{name}  {}", Self::line(day, &locale)));
+            if !day.bought {
+                days.push_str("  ");
+                days.push_str(&locale.get("summary-missed-buy"));
+            }
+            days.push('\n');
+        }
+        days.push_str(&format!(
+            "\n{}  {}",
+            locale.get("summary-week"),
+            Self::line(&calendar.week_total(week), &locale)
+        ));
+
+        commands
+            .spawn((
+                WeekSummary,
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: Color::BLACK.with_a(0.6).into(),
+                    z_index: ZIndex::Global(layers::UI as i32),
+                    ..default()
+                },
+            ))
+            .with_children(|children| {
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(8.),
+                            padding: UiRect::all(Val::Px(12.)),
+                            ..default()
+                        },
+                        background_color: Color::rgba_u8(102, 45, 60, 240).into(),
+                        ..default()
+                    })
+                    .with_children(|card| {
+                        card.spawn(TextBundle::from_section(
+                            locale.format("summary-title", &[("week", &(week + 1))]),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 16.,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ));
+                        card.spawn(TextBundle::from_section(
+                            days,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 10.,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ));
+                        card.spawn(TextBundle::from_section(
+                            locale.get("summary-continue"),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 10.,
+                                color: Color::rgb(0.7, 0.7, 0.7),
+                            },
+                        ));
+                    });
+            });
+    }
+
+    fn line(day: &DayStats, locale: &Locale) -> String {
+        locale.format(
+            "summary-line",
+            &[
+                ("bought", &format!("{:>2}", day.items_bought)),
+                ("consumed", &format!("{:>2}", day.items_consumed)),
+                ("stress", &format!("{:+.0}", day.stress_change)),
+            ],
+        )
+    }
+
+    fn close(
+        q: Query<&Interaction, (Changed<Interaction>, With<WeekSummary>)>,
+        keyboard_input: Res<Input<KeyCode>>,
+        button_inputs: Res<Input<GamepadButton>>,
+        gamepads: Res<Gamepads>,
+        mut pause: ResMut<NextState<PauseState>>,
+    ) {
+        let clicked = q
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        let pressed = keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return])
+            || gamepads.iter().any(|gamepad| {
+                button_inputs.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+            });
+        if clicked || pressed {
+            pause.set(PauseState::Running);
+        }
+    }
+
+    fn despawn(mut commands: Commands, q: Query<Entity, With<WeekSummary>>) {
+        for e in &q {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,790 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    app::AppExit,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    catalog::ItemCatalog,
+    dialog::{DialogBox, ShownDialog},
+    difficulty::Difficulty,
+    game_state::GameState,
+    high_scores::format_time,
+    layers,
+    locale::Locale,
+    presets::Presets,
+    sim::{Balance, CategoryRules, Cue, CueAction, Goal, LevelRules, Sim, SimEvent},
+    storage,
+};
+
+const CAMPAIGN_PATH: &str = "main.campaign.ron";
+
+/// Levels with their own stacks, script and goal, played in order from the
+/// level select screen. Finished levels are saved between sessions.
+pub struct CampaignPlugin;
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Campaign>()
+            .init_asset_loader::<CampaignLoader>()
+            .init_resource::<CurrentLevel>()
+            .insert_resource(
+                storage::load::<CampaignProgress>(CampaignProgress::KEY).unwrap_or_default(),
+            )
+            .add_systems(Startup, load_campaign)
+            .add_systems(
+                Update,
+                wait_for_campaign.run_if(
+                    in_state(GameState::Loading)
+                        .and_then(resource_exists::<ItemCatalog>())
+                        .and_then(resource_exists::<CampaignHandle>()),
+                ),
+            )
+            .add_systems(OnEnter(GameState::StartScreen), CurrentLevel::clear)
+            .add_systems(OnEnter(GameState::Playing), GoalText::spawn)
+            .add_systems(OnEnter(GameState::LevelSelect), spawn_level_select)
+            .add_systems(OnExit(GameState::LevelSelect), despawn::<LevelSelectMarker>)
+            .add_systems(
+                Update,
+                (LevelButton::handle_clicks, back_to_title)
+                    .run_if(in_state(GameState::LevelSelect)),
+            )
+            .add_systems(
+                OnEnter(GameState::Victory),
+                (CampaignProgress::record, spawn_victory).chain(),
+            )
+            .add_systems(OnExit(GameState::Victory), despawn::<VictoryMarker>)
+            .add_systems(
+                Update,
+                VictoryButton::handle_clicks.run_if(in_state(GameState::Victory)),
+            );
+    }
+}
+
+/// Every level, in the order they unlock.
+#[derive(Asset, TypePath, Resource, Clone)]
+pub struct Campaign {
+    pub levels: Vec<Level>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Level {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Ids of the categories in play, every category when empty.
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Items each stack starts with, by category id. Missing stacks start empty.
+    #[serde(default)]
+    pub stacks: HashMap<String, usize>,
+    #[serde(default)]
+    pub script: Vec<ScriptedCue>,
+    pub goal: Goal,
+    /// The level is played by the chosen difficulty without one.
+    pub balance: Option<Balance>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ScriptedCue {
+    /// Seconds into the level.
+    pub at: f32,
+    pub action: ScriptedAction,
+}
+
+#[derive(Deserialize, Clone)]
+pub enum ScriptedAction {
+    /// Shows a line in the dialog box.
+    Dialog(String),
+    /// Buys this many items of the category with this id.
+    Buy(String, usize),
+}
+
+#[derive(Debug, Error)]
+pub enum LevelError {
+    #[error("level {level} uses category {category}, which is not in the catalog or not in play")]
+    UnknownCategory { level: String, category: String },
+    #[error("level {level} has a cue at {at} seconds, cues need a time of 0 or more")]
+    BadCueTime { level: String, at: f32 },
+}
+
+impl Level {
+    /// The categories in play and the rules the [`Sim`] runs the level by.
+    pub fn rules(
+        &self,
+        catalog: &ItemCatalog,
+    ) -> Result<(Vec<CategoryRules>, LevelRules), LevelError> {
+        let unknown = |category: &str| LevelError::UnknownCategory {
+            level: self.id.clone(),
+            category: category.to_string(),
+        };
+        let ids: Vec<&str> = if self.categories.is_empty() {
+            catalog.categories.iter().map(|c| c.id.as_str()).collect()
+        } else {
+            self.categories.iter().map(String::as_str).collect()
+        };
+        let all = catalog.rules();
+        let mut categories = Vec::new();
+        for id in &ids {
+            let index = catalog
+                .categories
+                .iter()
+                .position(|c| c.id == *id)
+                .ok_or_else(|| unknown(id))?;
+            categories.push(all[index].clone());
+        }
+        let in_play = |id: &str| ids.iter().position(|i| *i == id).ok_or_else(|| unknown(id));
+
+        for id in self.stacks.keys() {
+            in_play(id)?;
+        }
+        let stacks = ids
+            .iter()
+            .map(|id| self.stacks.get(*id).copied().unwrap_or(0))
+            .collect();
+        let mut script = Vec::new();
+        for cue in &self.script {
+            let action = match &cue.action {
+                ScriptedAction::Dialog(_) => CueAction::Dialog,
+                ScriptedAction::Buy(id, count) => CueAction::Buy {
+                    category: in_play(id)?,
+                    count: *count,
+                },
+            };
+            script.push(Cue {
+                at: self.cue_time(cue)?,
+                action,
+            });
+        }
+
+        Ok((
+            categories,
+            LevelRules {
+                stacks,
+                script,
+                goal: self.goal,
+            },
+        ))
+    }
+
+    /// The level's name in the current language. Levels without a
+    /// translation keep the name from their file.
+    pub fn title(&self, locale: &Locale) -> String {
+        locale.get_or(&format!("level-{}-name", self.id), &self.name)
+    }
+
+    pub fn summary(&self, locale: &Locale) -> String {
+        locale.get_or(&format!("level-{}-description", self.id), &self.description)
+    }
+
+    /// How far into the level `cue` comes up.
+    fn cue_time(&self, cue: &ScriptedCue) -> Result<Duration, LevelError> {
+        Duration::try_from_secs_f32(cue.at).map_err(|_| LevelError::BadCueTime {
+            level: self.id.clone(),
+            at: cue.at,
+        })
+    }
+
+    /// Dialog line of the script cue at `index`, if it is one.
+    pub fn cue_line(&self, index: usize, locale: &Locale) -> Option<String> {
+        match &self.script.get(index)?.action {
+            ScriptedAction::Dialog(line) => {
+                Some(locale.get_or(&format!("level-{}-cue-{index}", self.id), line))
+            }
+            ScriptedAction::Buy(..) => None,
+        }
+    }
+
+    /// One line saying what the level asks for.
+    pub fn goal_label(&self, locale: &Locale) -> String {
+        match self.goal {
+            Goal::Consume { items, days } => {
+                locale.format("goal-consume", &[("items", &items), ("days", &days)])
+            }
+            Goal::KeepStressUnder { stress, secs } => locale.format(
+                "goal-stress",
+                &[
+                    ("stress", &format!("{stress:.0}")),
+                    ("time", &format_time(Duration::from_secs_f32(secs))),
+                ],
+            ),
+        }
+    }
+}
+
+/// Index of the level being played, `None` in endless runs.
+#[derive(Resource, Default)]
+pub struct CurrentLevel(pub Option<usize>);
+
+impl CurrentLevel {
+    pub fn get<'a>(&self, campaign: &'a Campaign) -> Option<&'a Level> {
+        self.0.and_then(|index| campaign.levels.get(index))
+    }
+
+    /// What the run plays by, the level's own balance or the chosen difficulty.
+    pub fn balance(
+        &self,
+        campaign: &Campaign,
+        difficulty: &Difficulty,
+        presets: &Presets,
+    ) -> Balance {
+        match self.get(campaign) {
+            Some(Level {
+                id,
+                balance: Some(balance),
+                ..
+            }) => presets.or_normal(*balance, &format!("level {id}")),
+            _ => difficulty.balance(presets),
+        }
+    }
+
+    /// Runs started from the title screen are endless.
+    pub fn clear(mut current: ResMut<CurrentLevel>) {
+        current.0 = None;
+    }
+}
+
+/// Ids of the finished levels.
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct CampaignProgress {
+    pub completed: Vec<String>,
+}
+
+impl CampaignProgress {
+    const KEY: &'static str = "campaign";
+
+    /// The first level is always open, later ones once the one before is finished.
+    pub fn is_unlocked(&self, campaign: &Campaign, index: usize) -> bool {
+        index == 0
+            || campaign
+                .levels
+                .get(index - 1)
+                .is_some_and(|level| self.completed.contains(&level.id))
+    }
+
+    fn record(
+        mut progress: ResMut<CampaignProgress>,
+        campaign: Res<Campaign>,
+        current: Res<CurrentLevel>,
+    ) {
+        let Some(level) = current.get(&campaign) else {
+            return;
+        };
+        if !progress.completed.contains(&level.id) {
+            progress.completed.push(level.id.clone());
+            storage::save(Self::KEY, &*progress);
+        }
+    }
+}
+
+/// Wins the level once its goal is met.
+pub fn check_goal(sim: Res<Sim>, mut state: ResMut<NextState<GameState>>) {
+    if sim.won() && !sim.failed() {
+        state.set(GameState::Victory);
+    }
+}
+
+/// Shows the dialog lines of the level script as they come up.
+pub fn show_cue_dialogs(
+    mut events: EventReader<SimEvent>,
+    mut dialog_box: Query<&mut DialogBox>,
+    mut shown_dialog: ResMut<ShownDialog>,
+    campaign: Res<Campaign>,
+    current: Res<CurrentLevel>,
+    locale: Res<Locale>,
+) {
+    let Some(level) = current.get(&campaign) else {
+        return;
+    };
+    for event in events.read() {
+        let SimEvent::Cue(index) = *event else {
+            continue;
+        };
+        if let Some(line) = level.cue_line(index, &locale) {
+            dialog_box.single_mut().timer.reset();
+            shown_dialog.0 = Some(line);
+        }
+    }
+}
+
+/// Progress towards the level goal, at the top of the screen.
+#[derive(Component)]
+pub struct GoalText;
+
+impl GoalText {
+    fn spawn(
+        mut commands: Commands,
+        locale: Res<Locale>,
+        campaign: Res<Campaign>,
+        current: Res<CurrentLevel>,
+    ) {
+        if current.get(&campaign).is_none() {
+            return;
+        }
+        commands.spawn((
+            GoalText,
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 165., layers::UI + 10.),
+                ..default()
+            },
+        ));
+    }
+
+    pub fn update(sim: Res<Sim>, locale: Res<Locale>, mut q: Query<&mut Text, With<GoalText>>) {
+        let Some(level) = &sim.level else {
+            return;
+        };
+        let label = match level.goal {
+            Goal::Consume { items, days } => locale.format(
+                "goal-consume-progress",
+                &[
+                    ("consumed", &sim.consumed.total),
+                    ("items", &items),
+                    ("day", &(sim.calendar.day() + 1).min(days)),
+                    ("days", &days),
+                ],
+            ),
+            Goal::KeepStressUnder { stress, secs } => {
+                let left = Duration::from_secs_f32(secs).saturating_sub(sim.elapsed);
+                locale.format(
+                    "goal-stress-progress",
+                    &[
+                        ("stress", &format!("{stress:.0}")),
+                        ("time", &format_time(left)),
+                    ],
+                )
+            }
+        };
+        for mut text in &mut q {
+            if text.sections[0].value != label {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct LevelSelectMarker;
+
+/// Starts the level at this index.
+#[derive(Component)]
+struct LevelButton(usize);
+
+impl LevelButton {
+    fn handle_clicks(
+        q: Query<(&Interaction, &LevelButton), Changed<Interaction>>,
+        mut current: ResMut<CurrentLevel>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        for (interaction, button) in &q {
+            if *interaction == Interaction::Pressed {
+                current.0 = Some(button.0);
+                state.set(GameState::Playing);
+            }
+        }
+    }
+}
+
+/// Goes back to the title screen from the level select screen.
+#[derive(Component)]
+struct BackButton;
+
+fn back_to_title(
+    q: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    button_inputs: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let clicked = q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    let pressed = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepads.iter().any(|gamepad| {
+            button_inputs.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East))
+        });
+    if clicked || pressed {
+        state.set(GameState::StartScreen);
+    }
+}
+
+fn spawn_level_select(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    campaign: Res<Campaign>,
+    progress: Res<CampaignProgress>,
+) {
+    let font = locale.font.clone();
+    commands
+        .spawn((LevelSelectMarker, menu_root()))
+        .with_children(|children| {
+            children.spawn(TextBundle::from_section(
+                locale.get("campaign-title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 24.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+
+            for (index, level) in campaign.levels.iter().enumerate() {
+                let unlocked = progress.is_unlocked(&campaign, index);
+                let done = progress.completed.contains(&level.id);
+                let mut button = children.spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(280.),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(4.)),
+                        ..default()
+                    },
+                    background_color: if unlocked {
+                        Color::DARK_GRAY.into()
+                    } else {
+                        Color::DARK_GRAY.with_a(0.4).into()
+                    },
+                    ..default()
+                });
+                if unlocked {
+                    button.insert(LevelButton(index));
+                }
+                let color = if unlocked {
+                    Color::rgb(0.9, 0.9, 0.9)
+                } else {
+                    Color::rgb(0.5, 0.5, 0.5)
+                };
+                button.with_children(|parent| {
+                    let key = match (unlocked, done) {
+                        (false, _) => "campaign-locked",
+                        (true, false) => "campaign-level",
+                        (true, true) => "campaign-level-done",
+                    };
+                    let name = locale.format(
+                        key,
+                        &[("number", &(index + 1)), ("name", &level.title(&locale))],
+                    );
+                    parent.spawn(TextBundle::from_section(
+                        name,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.,
+                            color,
+                        },
+                    ));
+                    if unlocked {
+                        parent.spawn(TextBundle::from_section(
+                            format!("{}\n{}", level.summary(&locale), level.goal_label(&locale)),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 10.,
+                                color,
+                            },
+                        ));
+                    }
+                });
+            }
+
+            spawn_button(
+                children,
+                BackButton,
+                &locale.get("campaign-back"),
+                font.clone(),
+            );
+        });
+}
+
+#[derive(Component)]
+struct VictoryMarker;
+
+#[derive(Component, Clone, Copy)]
+enum VictoryButton {
+    NextLevel,
+    LevelSelect,
+    Title,
+}
+
+impl VictoryButton {
+    /// Key of the button's text.
+    fn label(self) -> &'static str {
+        match self {
+            VictoryButton::NextLevel => "victory-next-level",
+            VictoryButton::LevelSelect => "victory-level-select",
+            VictoryButton::Title => "victory-title-screen",
+        }
+    }
+
+    fn handle_clicks(
+        q: Query<(&Interaction, &VictoryButton), Changed<Interaction>>,
+        mut current: ResMut<CurrentLevel>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        for (interaction, button) in &q {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            match button {
+                VictoryButton::NextLevel => {
+                    current.0 = current.0.map(|index| index + 1);
+                    state.set(GameState::Playing);
+                }
+                VictoryButton::LevelSelect => state.set(GameState::LevelSelect),
+                VictoryButton::Title => state.set(GameState::StartScreen),
+            }
+        }
+    }
+}
+
+fn spawn_victory(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    campaign: Res<Campaign>,
+    current: Res<CurrentLevel>,
+    sim: Res<Sim>,
+) {
+    let font = locale.font.clone();
+    let name = current
+        .get(&campaign)
+        .map_or_else(String::new, |level| level.title(&locale));
+    let has_next = current
+        .0
+        .is_some_and(|index| index + 1 < campaign.levels.len());
+    let stats = locale.format(
+        "victory-stats",
+        &[
+            ("consumed", &sim.consumed.total),
+            ("time", &format_time(sim.elapsed)),
+            ("days", &(sim.calendar.day() + 1)),
+        ],
+    );
+
+    commands
+        .spawn((VictoryMarker, menu_root()))
+        .with_children(|children| {
+            children.spawn(TextBundle::from_section(
+                locale.format("victory-heading", &[("name", &name)]),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 24.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+            children.spawn(TextBundle::from_section(
+                stats,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 16.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+
+            if has_next {
+                spawn_button(
+                    children,
+                    VictoryButton::NextLevel,
+                    &locale.get(VictoryButton::NextLevel.label()),
+                    font.clone(),
+                );
+            }
+            for button in [VictoryButton::LevelSelect, VictoryButton::Title] {
+                spawn_button(children, button, &locale.get(button.label()), font.clone());
+            }
+        });
+}
+
+/// Full screen column the campaign screens are laid out in.
+pub fn menu_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        background_color: Color::rgb_u8(102, 45, 60).into(),
+        ..default()
+    }
+}
+
+pub fn spawn_button(
+    children: &mut ChildBuilder,
+    marker: impl Component,
+    label: &str,
+    font: Handle<Font>,
+) {
+    children
+        .spawn((
+            marker,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(160.),
+                    height: Val::Px(30.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font,
+                    font_size: 16.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+        });
+}
+
+pub fn despawn<T: Component>(mut commands: Commands, q: Query<Entity, With<T>>) {
+    for e in &q {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+#[derive(Resource)]
+struct CampaignHandle(Handle<Campaign>);
+
+fn load_campaign(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CampaignHandle(asset_server.load(CAMPAIGN_PATH)));
+}
+
+/// Checks every level against the catalog before the campaign is playable.
+fn wait_for_campaign(
+    mut commands: Commands,
+    handle: Res<CampaignHandle>,
+    asset_server: Res<AssetServer>,
+    campaigns: Res<Assets<Campaign>>,
+    catalog: Res<ItemCatalog>,
+    mut exit: EventWriter<AppExit>,
+) {
+    match asset_server.get_load_state(&handle.0) {
+        Some(LoadState::Loaded) => {
+            let Some(campaign) = campaigns.get(&handle.0) else {
+                return;
+            };
+            for level in &campaign.levels {
+                if let Err(e) = level.rules(&catalog) {
+                    error!("could not load {CAMPAIGN_PATH}: {e}");
+                    exit.send(AppExit);
+                    return;
+                }
+            }
+            commands.insert_resource(campaign.clone());
+            commands.remove_resource::<CampaignHandle>();
+        }
+        Some(LoadState::Failed) => {
+            error!("could not load {CAMPAIGN_PATH}, see the asset error above");
+            exit.send(AppExit);
+        }
+        _ => {}
+    }
+}
+
+#[derive(Deserialize)]
+struct CampaignFile {
+    /// Paths of the level files, in order.
+    levels: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum CampaignLoaderError {
+    #[error("could not read the campaign: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the campaign: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("could not read level {path}: {reason}")]
+    Level { path: String, reason: String },
+    #[error("the campaign has no levels")]
+    Empty,
+    #[error("level {0} is listed more than once")]
+    DuplicateLevel(String),
+    #[error("the script of level {0} is out of order, cues have to be in order of time")]
+    ScriptOutOfOrder(String),
+    #[error("level {0} has a goal that can't be met")]
+    InvalidGoal(String),
+    #[error(transparent)]
+    InvalidLevel(#[from] LevelError),
+}
+
+/// Loads `*.campaign.ron` files along with every level file they list.
+#[derive(Default)]
+pub struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader {
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = CampaignLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Campaign, CampaignLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: CampaignFile = ron::de::from_bytes(&bytes)?;
+            if file.levels.is_empty() {
+                return Err(CampaignLoaderError::Empty);
+            }
+
+            let mut levels: Vec<Level> = Vec::new();
+            for path in file.levels {
+                let to_error = |reason: String| CampaignLoaderError::Level {
+                    path: path.clone(),
+                    reason,
+                };
+                let bytes = load_context
+                    .read_asset_bytes(path.clone())
+                    .await
+                    .map_err(|e| to_error(e.to_string()))?;
+                let level: Level =
+                    ron::de::from_bytes(&bytes).map_err(|e| to_error(e.to_string()))?;
+
+                if levels.iter().any(|l| l.id == level.id) {
+                    return Err(CampaignLoaderError::DuplicateLevel(level.id));
+                }
+                for cue in &level.script {
+                    level.cue_time(cue)?;
+                }
+                if level.script.windows(2).any(|pair| pair[1].at < pair[0].at) {
+                    return Err(CampaignLoaderError::ScriptOutOfOrder(level.id));
+                }
+                let possible = match level.goal {
+                    Goal::Consume { items, days } => items > 0 && days > 0,
+                    Goal::KeepStressUnder { stress, secs } => stress > 0. && secs > 0.,
+                };
+                if !possible {
+                    return Err(CampaignLoaderError::InvalidGoal(level.id));
+                }
+                levels.push(level);
+            }
+
+            Ok(Campaign { levels })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
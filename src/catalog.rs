@@ -0,0 +1,476 @@
+use std::time::Duration;
+
+use bevy::{
+    app::AppExit,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    game_state::GameState,
+    item::ItemType,
+    locale::Locale,
+    sim::{CategoryRules, GeneratedRules, ItemRules},
+    titles::{TitleArea, TitlePrinter, TitleWords},
+};
+
+pub const CATALOG_PATH: &str = "items.catalog.ron";
+/// Font titles are printed with.
+const TITLE_FONT: &str = "chevyray_bird_seed.ttf";
+
+/// Loads the item catalog. The game stays in [`GameState::Loading`] until it's ready.
+pub struct CatalogPlugin;
+impl Plugin for CatalogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ItemCatalog>()
+            .init_asset_loader::<ItemCatalogLoader>()
+            .add_systems(Startup, load_catalog)
+            .add_systems(
+                Update,
+                wait_for_catalog.run_if(
+                    in_state(GameState::Loading).and_then(resource_exists::<CatalogHandle>()),
+                ),
+            );
+    }
+}
+
+/// Every item that can be bought, grouped by category.
+#[derive(Asset, TypePath, Resource, Clone)]
+pub struct ItemCatalog {
+    pub categories: Vec<CatalogCategory>,
+    pub title_words: TitleWords,
+    pub printer: TitlePrinter,
+}
+
+#[derive(Clone)]
+pub struct CatalogCategory {
+    pub id: String,
+    /// Shown when the current language has no name for the category.
+    pub label: String,
+    /// Tint of the label box under the stack.
+    pub color: Color,
+    /// Width and height an item takes on the stack.
+    pub dimensions: Vec2,
+    /// Consume time of generated items.
+    pub consume_time: Duration,
+    pub items: Vec<CatalogItem>,
+    pub generated: Option<GeneratedItems>,
+}
+
+/// Items made by printing a generated title on blank art.
+#[derive(Clone)]
+pub struct GeneratedItems {
+    pub blanks: Vec<BlankArt>,
+    /// Where titles go on the spine.
+    pub side_area: TitleArea,
+    /// Where titles go on the cover.
+    pub cover_area: TitleArea,
+    /// How likely a generated item is to be bought compared to the listed ones.
+    pub weight: f32,
+}
+
+#[derive(Clone)]
+pub struct BlankArt {
+    pub side: Handle<Image>,
+    pub cover: Handle<Image>,
+}
+
+#[derive(Clone)]
+pub struct CatalogItem {
+    /// Spine shown while the item is on a stack.
+    pub side: Handle<Image>,
+    /// Cover shown while the item is dragged, queued or consumed.
+    pub cover: Handle<Image>,
+    pub title: String,
+    pub height: f32,
+    pub consume_time: Duration,
+    /// How likely this item is to be bought compared to the rest of its category.
+    pub weight: f32,
+    /// The art is blank and `title` is printed on it.
+    pub print_title: bool,
+}
+
+impl CatalogCategory {
+    /// The category's name in the current language.
+    pub fn name(&self, locale: &Locale) -> String {
+        locale.get_or(&format!("category-{}", self.id), &self.label)
+    }
+}
+
+impl ItemCatalog {
+    pub fn category(&self, item_type: ItemType) -> &CatalogCategory {
+        &self.categories[item_type.0]
+    }
+
+    pub fn item_types(&self) -> impl Iterator<Item = ItemType> {
+        (0..self.categories.len()).map(ItemType)
+    }
+
+    /// The parts of the catalog the [`Sim`](crate::sim::Sim) needs to run.
+    pub fn rules(&self) -> Vec<CategoryRules> {
+        category_rules(&self.categories)
+    }
+}
+
+/// The rules of `categories`, for the loaded catalog and [`read_rules`] alike.
+fn category_rules(categories: &[CatalogCategory]) -> Vec<CategoryRules> {
+    categories
+        .iter()
+        .enumerate()
+        .map(|(i, category)| CategoryRules {
+            item_type: ItemType(i),
+            items: category
+                .items
+                .iter()
+                .map(|item| ItemRules {
+                    height: item.height,
+                    consume_time: item.consume_time,
+                    weight: item.weight,
+                })
+                .collect(),
+            generated: category.generated.as_ref().map(|generated| GeneratedRules {
+                blanks: generated.blanks.len(),
+                rules: ItemRules {
+                    height: category.dimensions.y,
+                    consume_time: category.consume_time,
+                    weight: generated.weight,
+                },
+            }),
+        })
+        .collect()
+}
+
+#[derive(Resource)]
+struct CatalogHandle(Handle<ItemCatalog>);
+
+fn load_catalog(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CatalogHandle(asset_server.load(CATALOG_PATH)));
+}
+
+fn wait_for_catalog(
+    mut commands: Commands,
+    handle: Res<CatalogHandle>,
+    asset_server: Res<AssetServer>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    match asset_server.get_load_state(&handle.0) {
+        Some(LoadState::Loaded) => {
+            let Some(catalog) = catalogs.get(&handle.0) else {
+                return;
+            };
+            commands.insert_resource(catalog.clone());
+            commands.remove_resource::<CatalogHandle>();
+        }
+        Some(LoadState::Failed) => {
+            error!("could not load {CATALOG_PATH}, see the asset error above");
+            exit.send(AppExit);
+        }
+        _ => {}
+    }
+}
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    categories: Vec<CategoryFile>,
+    #[serde(default)]
+    titles: TitleWords,
+}
+
+#[derive(Deserialize)]
+struct CategoryFile {
+    id: String,
+    label: String,
+    color: (u8, u8, u8),
+    dimensions: (f32, f32),
+    /// seconds
+    consume_time: f32,
+    #[serde(default)]
+    items: Vec<ItemFile>,
+    generated: Option<GeneratedFile>,
+}
+
+#[derive(Deserialize)]
+struct GeneratedFile {
+    #[serde(default = "default_weight")]
+    weight: f32,
+    side_area: TitleArea,
+    cover_area: TitleArea,
+    /// Side and cover art pairs.
+    blanks: Vec<(String, String)>,
+}
+
+#[derive(Deserialize)]
+struct ItemFile {
+    side: String,
+    cover: String,
+    title: String,
+    /// Defaults to the category's height.
+    height: Option<f32>,
+    /// Defaults to the category's consume time.
+    consume_time: Option<f32>,
+    #[serde(default = "default_weight")]
+    weight: f32,
+    #[serde(default)]
+    print_title: bool,
+}
+
+fn default_weight() -> f32 {
+    1.
+}
+
+impl CatalogFile {
+    /// Turns down a catalog the game can't play with, before any art is loaded.
+    fn check(&self) -> Result<(), CatalogLoaderError> {
+        if self.categories.is_empty() {
+            return Err(CatalogLoaderError::NoCategories);
+        }
+        if let Some(missing) = self.titles.missing_lists().into_iter().next() {
+            return Err(CatalogLoaderError::MissingWords(missing));
+        }
+        for (i, category) in self.categories.iter().enumerate() {
+            let blanks = category.generated.as_ref().map_or(0, |g| g.blanks.len());
+            if category.items.is_empty() && blanks == 0 {
+                return Err(CatalogLoaderError::EmptyCategory(category.id.clone()));
+            }
+            if category.items.iter().any(|item| item.print_title) && category.generated.is_none() {
+                return Err(CatalogLoaderError::NoTitleAreas(category.id.clone()));
+            }
+            if self.categories[..i].iter().any(|c| c.id == category.id) {
+                return Err(CatalogLoaderError::DuplicateCategory(category.id.clone()));
+            }
+            let (width, height) = category.dimensions;
+            if !(is_size(width) && is_size(height) && is_duration(category.consume_time)) {
+                return Err(CatalogLoaderError::BadSize(category.id.clone()));
+            }
+            for item in &category.items {
+                let height = item.height.is_none_or(is_size);
+                let consume_time = item.consume_time.is_none_or(is_duration);
+                if !(height && consume_time) {
+                    return Err(CatalogLoaderError::BadItem {
+                        category: category.id.clone(),
+                        title: item.title.clone(),
+                    });
+                }
+            }
+            let weights = category.items.iter().map(|item| item.weight);
+            let mut weights = weights.chain(category.generated.as_ref().map(|g| g.weight));
+            if !weights.clone().all(|w| w.is_finite() && w >= 0.) || weights.all(|w| w == 0.) {
+                return Err(CatalogLoaderError::BadWeights(category.id.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_size(size: f32) -> bool {
+    size.is_finite() && size > 0.
+}
+
+fn is_duration(secs: f32) -> bool {
+    secs > 0. && Duration::try_from_secs_f32(secs).is_ok()
+}
+
+impl CategoryFile {
+    /// The category with its art, `art` holding the side and cover of each of
+    /// its items in order.
+    fn into_category(
+        self,
+        art: Vec<(Handle<Image>, Handle<Image>)>,
+        blanks: Vec<BlankArt>,
+    ) -> CatalogCategory {
+        let items = self
+            .items
+            .iter()
+            .zip(art)
+            .map(|(item, (side, cover))| CatalogItem {
+                side,
+                cover,
+                title: item.title.clone(),
+                height: item.height.unwrap_or(self.dimensions.1),
+                consume_time: Duration::from_secs_f32(
+                    item.consume_time.unwrap_or(self.consume_time),
+                ),
+                weight: item.weight,
+                print_title: item.print_title,
+            })
+            .collect();
+        let (r, g, b) = self.color;
+        CatalogCategory {
+            id: self.id,
+            label: self.label,
+            color: Color::rgb_u8(r, g, b),
+            dimensions: Vec2::new(self.dimensions.0, self.dimensions.1),
+            consume_time: Duration::from_secs_f32(self.consume_time),
+            items,
+            generated: self.generated.map(|generated| GeneratedItems {
+                blanks,
+                side_area: generated.side_area,
+                cover_area: generated.cover_area,
+                weight: generated.weight,
+            }),
+        }
+    }
+}
+
+/// The rules of a `*.catalog.ron` file without loading any of its art, for
+/// running the [`Sim`](crate::sim::Sim) outside the game.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_rules(bytes: &[u8]) -> Result<Vec<CategoryRules>, CatalogLoaderError> {
+    let file: CatalogFile = ron::de::from_bytes(bytes)?;
+    file.check()?;
+    let categories: Vec<CatalogCategory> = file
+        .categories
+        .into_iter()
+        .map(|category| {
+            let art = vec![Default::default(); category.items.len()];
+            let blanks = category.generated.as_ref().map_or(0, |g| g.blanks.len());
+            let blanks = vec![
+                BlankArt {
+                    side: Handle::default(),
+                    cover: Handle::default(),
+                };
+                blanks
+            ];
+            category.into_category(art, blanks)
+        })
+        .collect();
+    Ok(category_rules(&categories))
+}
+
+#[derive(Debug, Error)]
+pub enum CatalogLoaderError {
+    #[error("could not read the catalog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the catalog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("could not load texture {path} for \"{title}\": {reason}")]
+    Texture {
+        path: String,
+        title: String,
+        reason: String,
+    },
+    #[error("could not load font {path}: {reason}")]
+    Font { path: String, reason: String },
+    #[error("the catalog has no categories")]
+    NoCategories,
+    #[error("category {0} has no items")]
+    EmptyCategory(String),
+    #[error("category {0} prints titles but has no title areas, add a `generated` section")]
+    NoTitleAreas(String),
+    #[error("titles can't be printed on {0}, blank art has to be 8-bit RGBA")]
+    NotPrintable(String),
+    #[error("title patterns use {{{0}}} but there is no word list for it")]
+    MissingWords(String),
+    #[error("category {0} is listed more than once")]
+    DuplicateCategory(String),
+    #[error("category {0} needs a positive size and consume time")]
+    BadSize(String),
+    #[error("\"{title}\" in category {category} needs a positive height and consume time")]
+    BadItem { category: String, title: String },
+    #[error("category {0} needs weights of 0 or more that aren't all 0")]
+    BadWeights(String),
+}
+
+/// Loads `*.catalog.ron` files. Every texture is loaded up front so a missing
+/// one fails the whole catalog instead of showing up as a blank sprite.
+#[derive(Default)]
+pub struct ItemCatalogLoader;
+
+impl AssetLoader for ItemCatalogLoader {
+    type Asset = ItemCatalog;
+    type Settings = ();
+    type Error = CatalogLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<ItemCatalog, CatalogLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: CatalogFile = ron::de::from_bytes(&bytes)?;
+            file.check()?;
+            let printer = load_printer(load_context).await?;
+
+            let mut categories: Vec<CatalogCategory> = Vec::new();
+            for category in file.categories {
+                let mut art = Vec::new();
+                for item in &category.items {
+                    let side =
+                        load_texture(load_context, &item.side, &item.title, item.print_title)
+                            .await?;
+                    let cover =
+                        load_texture(load_context, &item.cover, &item.title, item.print_title)
+                            .await?;
+                    art.push((side, cover));
+                }
+                let mut blanks = Vec::new();
+                if let Some(generated) = &category.generated {
+                    for (side, cover) in &generated.blanks {
+                        blanks.push(BlankArt {
+                            side: load_texture(load_context, side, "blank", true).await?,
+                            cover: load_texture(load_context, cover, "blank", true).await?,
+                        });
+                    }
+                }
+                categories.push(category.into_category(art, blanks));
+            }
+
+            Ok(ItemCatalog {
+                categories,
+                title_words: file.titles,
+                printer,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["catalog.ron"]
+    }
+}
+
+async fn load_texture<'a>(
+    load_context: &mut LoadContext<'a>,
+    path: &str,
+    title: &str,
+    printable: bool,
+) -> Result<Handle<Image>, CatalogLoaderError> {
+    let to_error = |reason: String| CatalogLoaderError::Texture {
+        path: path.to_string(),
+        title: title.to_string(),
+        reason,
+    };
+    let image = load_context
+        .load_direct(path.to_string())
+        .await
+        .map_err(|e| to_error(e.to_string()))?
+        .take::<Image>()
+        .ok_or_else(|| to_error("not an image".to_string()))?;
+    if printable && !TitlePrinter::can_print_on(&image) {
+        return Err(CatalogLoaderError::NotPrintable(path.to_string()));
+    }
+
+    Ok(load_context.add_labeled_asset(path.to_string(), image))
+}
+
+async fn load_printer<'a>(
+    load_context: &mut LoadContext<'a>,
+) -> Result<TitlePrinter, CatalogLoaderError> {
+    let to_error = |reason: String| CatalogLoaderError::Font {
+        path: TITLE_FONT.to_string(),
+        reason,
+    };
+    let font = load_context
+        .load_direct(TITLE_FONT)
+        .await
+        .map_err(|e| to_error(e.to_string()))?
+        .take::<Font>()
+        .ok_or_else(|| to_error("not a font".to_string()))?;
+    Ok(TitlePrinter::new(font.font))
+}
@@ -1,24 +1,45 @@
 use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
 
-use crate::layers;
+use crate::{
+    item::ItemType,
+    layers,
+    locale::Locale,
+    sim::{Sim, Variant},
+};
 
-#[derive(Resource, Default)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ConsumeCount {
     pub total: u32,
-    pub books: ConsumeTypeCount,
-    pub comics: ConsumeTypeCount,
-    pub movies: ConsumeTypeCount,
-    pub games: ConsumeTypeCount,
+    pub by_type: HashMap<ItemType, ConsumeTypeCount>,
 }
 
-#[derive(Default)]
+impl ConsumeCount {
+    pub fn record(&mut self, item_type: ItemType, variant: Variant) {
+        self.total += 1;
+        let item_totals = self.by_type.entry(item_type).or_default();
+        item_totals.total += 1;
+        item_totals
+            .items
+            .entry(variant)
+            .and_modify(|c| *c += 1)
+            .or_insert(1);
+    }
+
+    pub fn of(&self, item_type: ItemType) -> Option<&ConsumeTypeCount> {
+        self.by_type.get(&item_type)
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ConsumeTypeCount {
     pub total: u32,
-    pub items: HashMap<usize, u32>,
+    /// Generated items are told apart by their title.
+    pub items: HashMap<Variant, u32>,
 }
 
 impl ConsumeTypeCount {
-    pub fn favorite(&self) -> Option<usize> {
+    pub fn favorite(&self) -> Option<Variant> {
         let favorite = self
             .items
             .iter()
@@ -45,7 +66,7 @@ pub struct CounterMarker;
 pub struct CounterText;
 
 impl CounterMarker {
-    pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>, locale: Res<Locale>) {
         commands
             .spawn((
                 CounterMarker,
@@ -62,7 +83,7 @@ impl CounterMarker {
                         text: Text::from_section(
                             "000000",
                             TextStyle {
-                                font: asset_server.load("chevyray_bird_seed.ttf"),
+                                font: locale.font.clone(),
                                 font_size: 13.,
                                 color: Color::WHITE,
                             },
@@ -80,11 +101,11 @@ impl CounterMarker {
         }
     }
 
-    pub fn update_counter(mut q: Query<&mut Text, With<CounterText>>, consumed: Res<ConsumeCount>) {
+    pub fn update_counter(mut q: Query<&mut Text, With<CounterText>>, sim: Res<Sim>) {
         let Ok(mut text) = q.get_single_mut() else {
             return;
         };
 
-        text.sections[0].value = format!("{:06}", consumed.total);
+        text.sections[0].value = format!("{:06}", sim.consumed.total);
     }
 }
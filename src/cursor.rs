@@ -0,0 +1,300 @@
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    game_state::{GameState, PauseState},
+    item::{ItemEntities, ReleaseItem},
+    layers,
+    queue::{AddToQueue, Queue},
+    sim::{ItemId, PlayerInput, Sim},
+    spawning::CircleButton,
+    stack::{AddToStack, RemoveFromStack, SpawnEvent, Stack},
+};
+
+/// Plays the run without a mouse. Arrow keys or the d-pad move a cursor
+/// between the stacks, the queue and the buy button, Space, Return or
+/// gamepad South picks up the top item of a stack and drops it where the
+/// cursor is, and Backspace or gamepad East puts it back.
+///
+/// The cursor sends the same commands as dragging with the mouse, so the rules
+/// can't tell the two apart.
+pub struct CursorPlugin;
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Cursor>()
+            .add_systems(OnEnter(GameState::Playing), Cursor::reset)
+            .add_systems(
+                Update,
+                (
+                    Cursor::hide_on_mouse,
+                    Cursor::navigate,
+                    Cursor::act,
+                    Cursor::carry,
+                    Cursor::draw,
+                )
+                    .chain()
+                    .in_set(PlayerInput)
+                    .run_if(in_state(GameState::Playing).and_then(in_state(PauseState::Running))),
+            );
+    }
+}
+
+/// What the cursor points at.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorTarget {
+    /// The stack with this index.
+    Stack(usize),
+    Queue,
+    BuyButton,
+}
+
+impl Default for CursorTarget {
+    fn default() -> Self {
+        CursorTarget::Stack(0)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Cursor {
+    pub target: CursorTarget,
+    /// Stack the cursor goes back to when leaving the queue or the buy button.
+    pub last_stack: usize,
+    /// Item picked up with the cursor and the stack it came from.
+    pub held: Option<(ItemId, usize)>,
+    /// Only shown once a key or button moved it, and hidden again when the
+    /// mouse moves.
+    pub visible: bool,
+}
+
+impl Cursor {
+    const BUY_BUTTON_SIZE: Vec2 = Vec2::splat(34.);
+
+    fn reset(mut cursor: ResMut<Cursor>) {
+        *cursor = Cursor::default();
+    }
+
+    fn hide_on_mouse(mut moved: EventReader<CursorMoved>, mut cursor: ResMut<Cursor>) {
+        if moved.read().count() > 0 && cursor.visible {
+            cursor.visible = false;
+        }
+    }
+
+    fn navigate(
+        keyboard_input: Res<Input<KeyCode>>,
+        button_inputs: Res<Input<GamepadButton>>,
+        gamepads: Res<Gamepads>,
+        sim: Res<Sim>,
+        mut cursor: ResMut<Cursor>,
+    ) {
+        let pressed = |key, button| {
+            keyboard_input.just_pressed(key)
+                || gamepads
+                    .iter()
+                    .any(|gamepad| button_inputs.just_pressed(GamepadButton::new(gamepad, button)))
+        };
+        let last = sim.stacks.len().saturating_sub(1);
+        let last_stack = cursor.last_stack.min(last);
+        let target = match cursor.target {
+            CursorTarget::Stack(i) => {
+                if pressed(KeyCode::Left, GamepadButtonType::DPadLeft) {
+                    CursorTarget::Stack(i.saturating_sub(1))
+                } else if pressed(KeyCode::Right, GamepadButtonType::DPadRight) {
+                    if i < last {
+                        CursorTarget::Stack(i + 1)
+                    } else {
+                        CursorTarget::BuyButton
+                    }
+                } else if pressed(KeyCode::Up, GamepadButtonType::DPadUp) {
+                    CursorTarget::BuyButton
+                } else if pressed(KeyCode::Down, GamepadButtonType::DPadDown) {
+                    CursorTarget::Queue
+                } else {
+                    return;
+                }
+            }
+            CursorTarget::Queue => {
+                if pressed(KeyCode::Up, GamepadButtonType::DPadUp)
+                    || pressed(KeyCode::Left, GamepadButtonType::DPadLeft)
+                    || pressed(KeyCode::Right, GamepadButtonType::DPadRight)
+                {
+                    CursorTarget::Stack(last_stack)
+                } else {
+                    return;
+                }
+            }
+            CursorTarget::BuyButton => {
+                if pressed(KeyCode::Left, GamepadButtonType::DPadLeft)
+                    || pressed(KeyCode::Down, GamepadButtonType::DPadDown)
+                {
+                    CursorTarget::Stack(last_stack)
+                } else {
+                    return;
+                }
+            }
+        };
+
+        cursor.visible = true;
+        cursor.target = target;
+        if let CursorTarget::Stack(i) = target {
+            cursor.last_stack = i;
+        }
+    }
+
+    fn act(
+        mut commands: Commands,
+        keyboard_input: Res<Input<KeyCode>>,
+        button_inputs: Res<Input<GamepadButton>>,
+        gamepads: Res<Gamepads>,
+        sim: Res<Sim>,
+        entities: Res<ItemEntities>,
+        stacks: Query<(Entity, &Stack)>,
+        mut cursor: ResMut<Cursor>,
+    ) {
+        let pressed = |keys: [KeyCode; 2], button| {
+            keyboard_input.any_just_pressed(keys)
+                || gamepads
+                    .iter()
+                    .any(|gamepad| button_inputs.just_pressed(GamepadButton::new(gamepad, button)))
+        };
+        let select = pressed([KeyCode::Space, KeyCode::Return], GamepadButtonType::South);
+        let cancel = pressed([KeyCode::Back, KeyCode::Delete], GamepadButtonType::East);
+        if !select && !cancel {
+            return;
+        }
+        cursor.visible = true;
+        let stack_entity = |index: usize| {
+            stacks
+                .iter()
+                .find(|(_, stack)| stack.index == index)
+                .map(|(e, _)| e)
+        };
+
+        // forget items that are gone
+        if let Some((id, _)) = cursor.held {
+            if !entities.0.contains_key(&id) {
+                cursor.held = None;
+            }
+        }
+
+        if cancel {
+            let Some((id, from)) = cursor.held.take() else {
+                return;
+            };
+            let (Some(e), Some(stack)) = (entities.0.get(&id), stack_entity(from)) else {
+                return;
+            };
+            commands.entity(*e).add(AddToStack(stack)).add(ReleaseItem);
+            return;
+        }
+
+        match (cursor.target, cursor.held) {
+            (CursorTarget::BuyButton, _) => commands.add(SpawnEvent),
+            (CursorTarget::Stack(i), None) => {
+                let Some(item) = sim.stacks.get(i).and_then(|stack| stack.items.last()) else {
+                    return;
+                };
+                let Some(e) = entities.0.get(&item.id) else {
+                    return;
+                };
+                commands
+                    .entity(*e)
+                    .insert(Pickable::IGNORE)
+                    .add(RemoveFromStack);
+                cursor.held = Some((item.id, i));
+            }
+            (CursorTarget::Stack(i), Some((id, _))) => {
+                let (Some(e), Some(stack)) = (entities.0.get(&id), stack_entity(i)) else {
+                    return;
+                };
+                commands.entity(*e).add(AddToStack(stack)).add(ReleaseItem);
+                cursor.held = None;
+            }
+            (CursorTarget::Queue, Some((id, _))) => {
+                let Some(e) = entities.0.get(&id) else {
+                    return;
+                };
+                // a full queue sends the item back to a stack, like a mouse drop
+                commands.entity(*e).add(AddToQueue).add(ReleaseItem);
+                cursor.held = None;
+            }
+            (CursorTarget::Queue, None) => {}
+        }
+    }
+
+    /// Center and size of what the cursor points at.
+    fn target_rect(
+        &self,
+        stacks: &Query<(&Stack, &Transform, &Sprite)>,
+        queue: &Query<&Transform, With<Queue>>,
+        buy_button: &Query<&Transform, With<CircleButton>>,
+    ) -> Option<(Vec2, Vec2)> {
+        match self.target {
+            CursorTarget::Stack(i) => {
+                let (_, transform, sprite) = stacks.iter().find(|(stack, ..)| stack.index == i)?;
+                let size = sprite.custom_size?;
+                let bottom = transform.translation.xy();
+                Some((bottom + Vec2::Y * size.y / 2., size))
+            }
+            CursorTarget::Queue => Some((queue.get_single().ok()?.translation.xy(), Queue::SIZE)),
+            CursorTarget::BuyButton => Some((
+                buy_button.get_single().ok()?.translation.xy(),
+                Self::BUY_BUTTON_SIZE,
+            )),
+        }
+    }
+
+    /// Floats the held item over the cursor.
+    fn carry(
+        cursor: Res<Cursor>,
+        sim: Res<Sim>,
+        entities: Res<ItemEntities>,
+        stacks: Query<(&Stack, &Transform, &Sprite)>,
+        queue: Query<&Transform, With<Queue>>,
+        buy_button: Query<&Transform, With<CircleButton>>,
+        mut items: Query<
+            &mut Transform,
+            (
+                With<ItemId>,
+                Without<Stack>,
+                Without<Queue>,
+                Without<CircleButton>,
+            ),
+        >,
+    ) {
+        let Some((id, _)) = cursor.held else {
+            return;
+        };
+        let Some(mut transform) = entities.0.get(&id).and_then(|e| items.get_mut(*e).ok()) else {
+            return;
+        };
+        let Some((center, size)) = cursor.target_rect(&stacks, &queue, &buy_button) else {
+            return;
+        };
+        let position = match cursor.target {
+            // just above the top item
+            CursorTarget::Stack(i) => {
+                let height = sim.stacks.get(i).map_or(0., |stack| stack.current_height());
+                center - Vec2::Y * size.y / 2. + Vec2::Y * (height + 30.)
+            }
+            CursorTarget::Queue => center + Vec2::Y * (size.y / 2. + 30.),
+            CursorTarget::BuyButton => center - Vec2::Y * (size.y / 2. + 30.),
+        };
+        transform.translation = position.extend(layers::ITEMS + 1.);
+    }
+
+    fn draw(
+        cursor: Res<Cursor>,
+        stacks: Query<(&Stack, &Transform, &Sprite)>,
+        queue: Query<&Transform, With<Queue>>,
+        buy_button: Query<&Transform, With<CircleButton>>,
+        mut gizmos: Gizmos,
+    ) {
+        if !cursor.visible {
+            return;
+        }
+        let Some((center, size)) = cursor.target_rect(&stacks, &queue, &buy_button) else {
+            return;
+        };
+        gizmos.rect_2d(center, 0., size + Vec2::splat(4.), Color::WHITE);
+    }
+}
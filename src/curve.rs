@@ -0,0 +1,193 @@
+use bevy::{
+    app::AppExit,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::game_state::GameState;
+
+pub const CURVE_PATH: &str = "endless.curve.ron";
+
+/// Loads the difficulty curve that makes endless runs harder over time.
+pub struct CurvePlugin;
+impl Plugin for CurvePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DifficultyCurve>()
+            .init_asset_loader::<DifficultyCurveLoader>()
+            .add_systems(Startup, load_curve)
+            .add_systems(
+                Update,
+                wait_for_curve.run_if(
+                    in_state(GameState::Loading).and_then(resource_exists::<CurveHandle>()),
+                ),
+            );
+    }
+}
+
+/// What the curve is measured against.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveKey {
+    /// Seconds since the run started.
+    Elapsed,
+    /// Items consumed so far.
+    Consumed,
+}
+
+/// How the rules shift at one point of a run. Everything is interpolated
+/// linearly between keyframes.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Keyframe {
+    /// Seconds or items, depending on the [`CurveKey`].
+    pub at: f32,
+    /// Multiplies the day length of the difficulty. Days are the buy interval.
+    pub day_length: f32,
+    /// Chance of a buy turning into a bulk purchase, 0-1.
+    pub bulk_chance: f32,
+    /// Smallest and largest number of items a bulk purchase brings.
+    pub bulk_size: (f32, f32),
+    /// Multiplies how long items take to consume.
+    pub consume_time: f32,
+    /// Multiplies the stress for items on the wrong stack.
+    pub mismatch_penalty: f32,
+}
+
+impl Keyframe {
+    fn lerp(self, to: Keyframe, t: f32) -> Keyframe {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Keyframe {
+            at: lerp(self.at, to.at),
+            day_length: lerp(self.day_length, to.day_length),
+            bulk_chance: lerp(self.bulk_chance, to.bulk_chance),
+            bulk_size: (
+                lerp(self.bulk_size.0, to.bulk_size.0),
+                lerp(self.bulk_size.1, to.bulk_size.1),
+            ),
+            consume_time: lerp(self.consume_time, to.consume_time),
+            mismatch_penalty: lerp(self.mismatch_penalty, to.mismatch_penalty),
+        }
+    }
+}
+
+#[derive(Asset, TypePath, Resource, Deserialize, Clone, Debug)]
+pub struct DifficultyCurve {
+    pub key: CurveKey,
+    /// In order of `at`.
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl DifficultyCurve {
+    /// The rules at `at`. Before the first keyframe and after the last the
+    /// curve stays flat.
+    pub fn sample(&self, at: f32) -> Keyframe {
+        let Some(next) = self.keyframes.iter().position(|k| k.at > at) else {
+            return self.keyframes[self.keyframes.len() - 1];
+        };
+        if next == 0 {
+            return self.keyframes[0];
+        }
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+        from.lerp(to, (at - from.at) / (to.at - from.at))
+    }
+
+    /// Reads a `*.curve.ron` file and checks the keyframes make sense.
+    pub fn parse(bytes: &[u8]) -> Result<DifficultyCurve, CurveLoaderError> {
+        let curve: DifficultyCurve = ron::de::from_bytes(bytes)?;
+
+        if curve.keyframes.is_empty() {
+            return Err(CurveLoaderError::Empty);
+        }
+        for pair in curve.keyframes.windows(2) {
+            if pair[1].at <= pair[0].at {
+                return Err(CurveLoaderError::OutOfOrder(pair[1].at));
+            }
+        }
+        for keyframe in &curve.keyframes {
+            let (min, max) = keyframe.bulk_size;
+            let valid = keyframe.day_length > 0.
+                && (0. ..=1.).contains(&keyframe.bulk_chance)
+                && min >= 1.
+                && max >= min
+                && keyframe.consume_time > 0.
+                && keyframe.mismatch_penalty >= 0.;
+            if !valid {
+                return Err(CurveLoaderError::Invalid(keyframe.at));
+            }
+        }
+
+        Ok(curve)
+    }
+}
+
+#[derive(Resource)]
+struct CurveHandle(Handle<DifficultyCurve>);
+
+fn load_curve(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CurveHandle(asset_server.load(CURVE_PATH)));
+}
+
+fn wait_for_curve(
+    mut commands: Commands,
+    handle: Res<CurveHandle>,
+    asset_server: Res<AssetServer>,
+    curves: Res<Assets<DifficultyCurve>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    match asset_server.get_load_state(&handle.0) {
+        Some(LoadState::Loaded) => {
+            let Some(curve) = curves.get(&handle.0) else {
+                return;
+            };
+            commands.insert_resource(curve.clone());
+            commands.remove_resource::<CurveHandle>();
+        }
+        Some(LoadState::Failed) => {
+            error!("could not load {CURVE_PATH}, see the asset error above");
+            exit.send(AppExit);
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CurveLoaderError {
+    #[error("could not read the curve: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the curve: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("the curve has no keyframes")]
+    Empty,
+    #[error("keyframe at {0} comes after a later one, keyframes have to be in order")]
+    OutOfOrder(f32),
+    #[error("keyframe at {0} has a negative or impossible value")]
+    Invalid(f32),
+}
+
+/// Loads `*.curve.ron` files.
+#[derive(Default)]
+pub struct DifficultyCurveLoader;
+
+impl AssetLoader for DifficultyCurveLoader {
+    type Asset = DifficultyCurve;
+    type Settings = ();
+    type Error = CurveLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<DifficultyCurve, CurveLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            DifficultyCurve::parse(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["curve.ron"]
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt::Write as _;
+
+use bevy::{input::InputSystem, prelude::*};
+use bevy_rand::prelude::*;
+use rand_core::RngCore;
+
+use crate::{
+    catalog::ItemCatalog, game_state::GameState, high_scores::format_time, item::ItemType, layers,
+    seed::RunSeed, sim::Sim,
+};
+
+/// A panel with the numbers behind the run and a console to push it into odd
+/// states, built with the `debug_console` feature. The backtick key opens and
+/// closes it. While it's open the keyboard goes to the console only.
+///
+/// The cheats bypass the recorder, so a recorded run that used them won't
+/// replay the same.
+pub struct DebugConsolePlugin;
+impl Plugin for DebugConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugConsole>()
+            .add_systems(OnEnter(GameState::Playing), DebugConsole::spawn)
+            .add_systems(OnExit(GameState::Playing), DebugConsole::despawn)
+            .add_systems(
+                PreUpdate,
+                DebugConsole::type_line
+                    .after(InputSystem)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                DebugConsole::show.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+const HELP: &str =
+    "commands: stress <value>, spawn <category> <count>, trigger bulk, skip day, consume";
+
+#[derive(Resource, Default)]
+struct DebugConsole {
+    open: bool,
+    /// What's been typed so far.
+    line: String,
+    /// What the last command did.
+    reply: String,
+}
+
+/// Something the console can do to the run.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Cheat {
+    Stress(f32),
+    /// Items of the catalog category at this index onto its stack.
+    Spawn {
+        category: usize,
+        count: usize,
+    },
+    TriggerBulk,
+    SkipDay,
+    Consume,
+}
+
+impl Cheat {
+    /// Reads a command. Categories can be shortened, `book` finds `books`.
+    fn parse(line: &str, catalog: &ItemCatalog) -> Result<Cheat, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["stress", value] => value
+                .parse()
+                .map(Cheat::Stress)
+                .map_err(|_| format!("{value} is not a number")),
+            ["spawn", category, rest @ ..] => {
+                let count = match rest {
+                    [] => 1,
+                    [count] => count
+                        .parse()
+                        .map_err(|_| format!("{count} is not a whole number"))?,
+                    _ => return Err(HELP.to_string()),
+                };
+                let category = catalog
+                    .categories
+                    .iter()
+                    .position(|c| c.id.starts_with(category))
+                    .ok_or_else(|| format!("there is no category {category}"))?;
+                Ok(Cheat::Spawn { category, count })
+            }
+            ["trigger", "bulk"] => Ok(Cheat::TriggerBulk),
+            ["skip", "day"] => Ok(Cheat::SkipDay),
+            ["consume"] => Ok(Cheat::Consume),
+            _ => Err(HELP.to_string()),
+        }
+    }
+
+    fn apply(self, sim: &mut Sim, rng: &mut impl RngCore) -> Result<(), String> {
+        match self {
+            Cheat::Stress(value) => sim.stress = value,
+            Cheat::Spawn { category, count } => {
+                // a level plays some of the catalog, so find where the run has it
+                let index = sim
+                    .categories
+                    .iter()
+                    .position(|c| c.item_type == ItemType(category))
+                    .ok_or_else(|| "not in this level".to_string())?;
+                sim.spawn(index, count, rng);
+            }
+            Cheat::TriggerBulk => sim.trigger_bulk(rng),
+            Cheat::SkipDay => sim.skip_day(),
+            Cheat::Consume => sim.finish_consuming(),
+        }
+        Ok(())
+    }
+}
+
+/// The panel's text.
+#[derive(Component)]
+struct DebugPanel;
+
+impl DebugConsole {
+    fn spawn(mut commands: Commands) {
+        let mut text = TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 12.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(4.),
+            top: Val::Px(4.),
+            padding: UiRect::all(Val::Px(4.)),
+            ..default()
+        })
+        .with_background_color(Color::BLACK.with_a(0.8));
+        text.z_index = ZIndex::Global(layers::UI as i32 + 100);
+        text.visibility = Visibility::Hidden;
+        commands.spawn((DebugPanel, text));
+    }
+
+    fn despawn(mut commands: Commands, q: Query<Entity, With<DebugPanel>>) {
+        for e in &q {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+
+    /// Takes the keyboard while the console is open, so typing doesn't also
+    /// play the game.
+    fn type_line(
+        mut console: ResMut<DebugConsole>,
+        mut keyboard_input: ResMut<Input<KeyCode>>,
+        mut chars: EventReader<ReceivedCharacter>,
+        mut sim: ResMut<Sim>,
+        mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+        catalog: Res<ItemCatalog>,
+    ) {
+        let typed: String = chars
+            .read()
+            .map(|event| event.char)
+            .filter(|c| !c.is_control() && *c != '`')
+            .collect();
+        if keyboard_input.just_pressed(KeyCode::Grave) {
+            console.open = !console.open;
+            keyboard_input.reset_all();
+            return;
+        }
+        if !console.open {
+            return;
+        }
+
+        console.line.push_str(&typed);
+        if keyboard_input.just_pressed(KeyCode::Back) {
+            console.line.pop();
+        }
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            console.open = false;
+        }
+        if keyboard_input.just_pressed(KeyCode::Return) {
+            let line = std::mem::take(&mut console.line);
+            console.reply = match Cheat::parse(&line, &catalog)
+                .and_then(|cheat| cheat.apply(&mut sim, &mut *rng))
+            {
+                Ok(()) => format!("ok: {}", line.trim()),
+                Err(e) => e,
+            };
+        }
+        keyboard_input.reset_all();
+    }
+
+    fn show(
+        console: Res<DebugConsole>,
+        sim: Res<Sim>,
+        seed: Res<RunSeed>,
+        catalog: Res<ItemCatalog>,
+        mut q: Query<(&mut Text, &mut Visibility), With<DebugPanel>>,
+    ) {
+        let Ok((mut text, mut visibility)) = q.get_single_mut() else {
+            return;
+        };
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if !console.open {
+            return;
+        }
+
+        let name = |item_type: ItemType| catalog.category(item_type).id.as_str();
+        let mut panel = String::new();
+        let _ = writeln!(
+            panel,
+            "stress {:.1} / {}",
+            sim.stress, sim.balance.fail_stress
+        );
+        let _ = writeln!(panel, "stack penalty {:.2}", sim.stack_penalty);
+        let _ = writeln!(
+            panel,
+            "day {} ends in {:.1}s of {:.1}s",
+            sim.calendar.day() + 1,
+            sim.today.remaining().as_secs_f32(),
+            sim.today.duration().as_secs_f32(),
+        );
+        for stack in &sim.stacks {
+            let _ = writeln!(
+                panel,
+                "{} stack {:.0} / {:.0}",
+                name(stack.item_type),
+                stack.current_height(),
+                sim.balance.max_stack_height,
+            );
+        }
+        let queue: Vec<&str> = sim.queue.iter().map(|item| name(item.item_type)).collect();
+        let _ = writeln!(
+            panel,
+            "queue [{}] of {}",
+            queue.join(", "),
+            sim.balance.max_queue_items
+        );
+        if let Some(consuming) = &sim.active {
+            let _ = writeln!(
+                panel,
+                "consuming {} {:.0}%",
+                name(consuming.item.item_type),
+                consuming.fraction() * 100.,
+            );
+        }
+        let _ = writeln!(panel, "time {}", format_time(sim.elapsed));
+        let _ = writeln!(panel, "seed {}", seed.current);
+        if !console.reply.is_empty() {
+            let _ = writeln!(panel, "{}", console.reply);
+        }
+        let _ = write!(panel, "> {}_", console.line);
+        text.sections[0].value = panel;
+    }
+}
@@ -3,7 +3,7 @@ use bevy_mod_picking::prelude::*;
 use bevy_rand::{prelude::ChaCha8Rng, resource::GlobalEntropy};
 use rand_core::RngCore;
 
-use crate::layers;
+use crate::{catalog::ItemCatalog, layers, locale::Locale, presets::Presets, sim::SimEvent};
 
 #[derive(Component)]
 pub struct DialogBox {
@@ -13,27 +13,26 @@ pub struct DialogBox {
 #[derive(Component)]
 pub struct DialogText;
 
+/// The player clicked the dialog box away.
+#[derive(Event)]
+pub struct DialogDismissed;
+
 #[derive(Resource)]
-pub struct ShownDialog(pub Option<&'static str>);
+pub struct ShownDialog(pub Option<String>);
 
 impl ShownDialog {
-    const DIALOGS: [&'static str; 7] = [
-        "Humble Bundle again...",
-        "Got a gift card!",
-        "Couldn't resist",
-        "It wasn't on sale, but...",
-        "I wanted to revist these.",
-        "Got paid!",
-        "Recommended by my friend",
-    ];
-
-    pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    pub fn spawn(
+        mut commands: Commands,
+        asset_server: Res<AssetServer>,
+        locale: Res<Locale>,
+        presets: Res<Presets>,
+    ) {
         commands.insert_resource(ShownDialog(None));
 
         commands
             .spawn((
                 DialogBox {
-                    timer: Timer::from_seconds(3.5, TimerMode::Once),
+                    timer: Timer::from_seconds(presets.dialog_time, TimerMode::Once),
                 },
                 SpriteBundle {
                     texture: asset_server.load("dialogbox.png"),
@@ -42,7 +41,7 @@ impl ShownDialog {
                 },
                 PickableBundle::default(),
                 On::<Pointer<Click>>::commands_mut(|_evt, commands| {
-                    commands.insert_resource(ShownDialog(None));
+                    commands.add(|world: &mut World| world.send_event(DialogDismissed));
                 }),
             ))
             .with_children(|children| {
@@ -50,9 +49,9 @@ impl ShownDialog {
                     DialogText,
                     Text2dBundle {
                         text: Text::from_section(
-                            "Placeholder!",
+                            "",
                             TextStyle {
-                                font: asset_server.load("chevyray_bird_seed.ttf"),
+                                font: locale.font.clone(),
                                 font_size: 16.,
                                 color: Color::WHITE,
                             },
@@ -81,7 +80,7 @@ impl ShownDialog {
         let (mut dialog_visible, mut dialog_box) = dialog_box.single_mut();
         if let Some(ref dialog) = shown_dialog.0 {
             *dialog_visible = Visibility::Visible;
-            dialog_text.single_mut().sections[0].value = dialog.to_string();
+            dialog_text.single_mut().sections[0].value = dialog.clone();
             if dialog_box.timer.tick(time.delta()).finished() {
                 shown_dialog.0 = None;
             }
@@ -90,9 +89,57 @@ impl ShownDialog {
         }
     }
 
-    pub fn new_random(rng: &mut GlobalEntropy<ChaCha8Rng>) -> Self {
-        let i = ((rng.next_u32() as f32 / u32::MAX as f32) * Self::DIALOGS.len() as f32 - 0.5)
-            .round() as usize;
-        Self(Some(Self::DIALOGS[i]))
+    pub fn dismiss(
+        mut events: EventReader<DialogDismissed>,
+        mut shown_dialog: ResMut<ShownDialog>,
+    ) {
+        if events.read().count() > 0 {
+            shown_dialog.0 = None;
+        }
+    }
+
+    /// Explains a bulk purchase with a random excuse.
+    pub fn show_bulk_purchase(
+        mut events: EventReader<SimEvent>,
+        mut dialog_box: Query<&mut DialogBox>,
+        mut shown_dialog: ResMut<ShownDialog>,
+        mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+        locale: Res<Locale>,
+    ) {
+        for event in events.read() {
+            if let SimEvent::BulkPurchase { .. } = event {
+                dialog_box.single_mut().timer.reset();
+                *shown_dialog = Self::new_random(&mut rng, locale.list("bulk-excuses"));
+            }
+        }
+    }
+
+    /// Announces sales so players can save their buys for them.
+    pub fn show_sales(
+        mut events: EventReader<SimEvent>,
+        mut dialog_box: Query<&mut DialogBox>,
+        mut shown_dialog: ResMut<ShownDialog>,
+        catalog: Res<ItemCatalog>,
+        locale: Res<Locale>,
+    ) {
+        for event in events.read() {
+            let (key, item_type) = match *event {
+                SimEvent::SaleAnnounced(item_type) => ("sale-announced", item_type),
+                SimEvent::SaleStarted(item_type) => ("sale-started", item_type),
+                SimEvent::SaleEnded(item_type) => ("sale-ended", item_type),
+                _ => continue,
+            };
+            let category = catalog.category(item_type).name(&locale);
+            let dialog = locale.format(key, &[("category", &category)]);
+            dialog_box.single_mut().timer.reset();
+            shown_dialog.0 = Some(dialog);
+        }
+    }
+
+    /// One of `dialogs` at random.
+    pub fn new_random(rng: &mut GlobalEntropy<ChaCha8Rng>, dialogs: &[String]) -> Self {
+        let i = ((rng.next_u32() as f32 / u32::MAX as f32) * dialogs.len() as f32 - 0.5).round()
+            as usize;
+        Self(dialogs.get(i).cloned())
     }
 }
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{presets::Presets, sim::Balance, storage};
+
+/// Picks how hard runs are. The choice is saved between sessions.
+pub struct DifficultyPlugin;
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Difficulty::load()).add_systems(
+            Update,
+            Difficulty::save.run_if(resource_changed::<Difficulty>()),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Preset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Plays by [`Difficulty::custom`]. The others are read from [`Presets`].
+    Custom,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [Preset::Easy, Preset::Normal, Preset::Hard, Preset::Custom];
+
+    /// Key of the preset's name.
+    pub fn label(self) -> &'static str {
+        match self {
+            Preset::Easy => "difficulty-easy",
+            Preset::Normal => "difficulty-normal",
+            Preset::Hard => "difficulty-hard",
+            Preset::Custom => "difficulty-custom",
+        }
+    }
+
+    /// The preset after this one, wrapping around.
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// The preset before this one, wrapping around.
+    pub fn previous(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// The chosen preset. Runs read their [`Balance`] from here when they start.
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Difficulty {
+    pub preset: Preset,
+    /// Numbers for [`Preset::Custom`], edited in the saved `difficulty.ron`.
+    pub custom: Balance,
+}
+
+impl Difficulty {
+    const KEY: &'static str = "difficulty";
+
+    /// The saved difficulty, or the default one.
+    pub fn load() -> Self {
+        storage::load(Self::KEY).unwrap_or_default()
+    }
+
+    pub fn balance(&self, presets: &Presets) -> Balance {
+        match self.preset {
+            Preset::Easy => presets.easy,
+            Preset::Normal => presets.normal,
+            Preset::Hard => presets.hard,
+            Preset::Custom => presets.or_normal(self.custom, "the custom difficulty"),
+        }
+    }
+
+    fn save(difficulty: Res<Difficulty>) {
+        storage::save(Self::KEY, &*difficulty);
+    }
+}
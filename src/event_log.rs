@@ -0,0 +1,165 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::Serialize;
+
+use crate::{
+    campaign::{Campaign, CurrentLevel},
+    catalog::ItemCatalog,
+    game_state::GameState,
+    launch,
+    seed::RunSeed,
+    sim::{Balance, Sim, SimEvent},
+};
+
+/// Logs every [`SimEvent`] as JSON lines for the balancing scripts, to the
+/// file given with `--events <path>` or, on the web with `?events`, as a
+/// download at the end of every run or when the game is closed.
+///
+/// Each run starts with a `run` line holding the seed, the level, the balance
+/// and the category ids that item types index into, followed by an `event`
+/// line per event with the run time and day it happened on.
+pub struct EventLogPlugin;
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Playing),
+            EventLog::finish.run_if(resource_exists::<EventLog>()),
+        )
+        .add_systems(
+            Last,
+            EventLog::finish_on_exit
+                .run_if(resource_exists::<EventLog>().and_then(in_state(GameState::Playing))),
+        );
+
+        if let Some(path) = launch::option("events") {
+            // every launch starts a fresh log
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Err(e) = std::fs::File::create(&path) {
+                error!("could not create {path}: {e}");
+                return;
+            }
+            app.insert_resource(EventLog {
+                path,
+                lines: String::new(),
+            });
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Line<'a> {
+    Run {
+        seed: u64,
+        /// Id of the campaign level played, `None` for endless runs.
+        level: Option<&'a str>,
+        balance: Balance,
+        categories: Vec<&'a str>,
+    },
+    Event {
+        /// Seconds since the run started.
+        time: f32,
+        day: usize,
+        event: &'a SimEvent,
+    },
+}
+
+/// Lines of the current run, written out when it ends.
+#[derive(Resource)]
+pub struct EventLog {
+    path: String,
+    lines: String,
+}
+
+impl EventLog {
+    pub fn write(
+        mut log: ResMut<EventLog>,
+        mut events: EventReader<SimEvent>,
+        sim: Res<Sim>,
+        seed: Res<RunSeed>,
+        campaign: Res<Campaign>,
+        current_level: Res<CurrentLevel>,
+        catalog: Res<ItemCatalog>,
+    ) {
+        if sim.is_added() {
+            log.push(&Line::Run {
+                seed: seed.current,
+                level: current_level.get(&campaign).map(|level| level.id.as_str()),
+                balance: sim.balance,
+                categories: catalog
+                    .categories
+                    .iter()
+                    .map(|category| category.id.as_str())
+                    .collect(),
+            });
+        }
+        for event in events.read() {
+            log.push(&Line::Event {
+                time: sim.elapsed.as_secs_f32(),
+                day: sim.calendar.day(),
+                event,
+            });
+        }
+    }
+
+    fn push(&mut self, line: &Line) {
+        match serde_json::to_string(line) {
+            Ok(json) => {
+                self.lines.push_str(&json);
+                self.lines.push('\n');
+            }
+            Err(e) => error!("could not log event: {e}"),
+        }
+    }
+
+    fn finish(mut log: ResMut<EventLog>) {
+        log.flush();
+    }
+
+    /// Keeps the run's lines when the game is closed in the middle of it.
+    fn finish_on_exit(mut log: ResMut<EventLog>, mut exit: EventReader<AppExit>) {
+        if exit.read().next().is_some() {
+            log.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let lines = std::mem::take(&mut self.lines);
+        if let Err(e) = export(&self.path, &lines) {
+            error!("could not write event log {}: {e}", self.path);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export(path: &str, lines: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+/// Offers the run's log as a file download.
+#[cfg(target_arch = "wasm32")]
+fn export(_path: &str, lines: &str) -> Result<(), String> {
+    use js_sys::wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&lines.into());
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("application/x-ndjson");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|e| format!("{e:?}"))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|e| format!("{e:?}"))?;
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?
+        .create_element("a")
+        .map_err(|e| format!("{e:?}"))?
+        .unchecked_into::<web_sys::HtmlAnchorElement>();
+    anchor.set_href(&url);
+    anchor.set_download("events.jsonl");
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(|e| format!("{e:?}"))
+}
@@ -1,6 +1,17 @@
 use bevy::prelude::*;
 
-use crate::{consume_counter::ConsumeCount, game_state::GameState, item::ItemHandles};
+use crate::{
+    campaign::{Campaign, CurrentLevel},
+    catalog::ItemCatalog,
+    difficulty::Difficulty,
+    game_state::GameState,
+    high_scores::{format_time, HighScores},
+    locale::Locale,
+    seed::RunSeed,
+    sim::Sim,
+    start_screen::StartButton,
+    titles::ItemArt,
+};
 
 pub struct FailScreenPlugin;
 impl Plugin for FailScreenPlugin {
@@ -16,13 +27,60 @@ struct FailMarker;
 fn spawn(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    counts: Res<ConsumeCount>,
-    handles: Res<ItemHandles>,
+    sim: Res<Sim>,
+    catalog: Res<ItemCatalog>,
+    mut art: ResMut<ItemArt>,
+    mut images: ResMut<Assets<Image>>,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    scores: Res<HighScores>,
+    campaign: Res<Campaign>,
+    current_level: Res<CurrentLevel>,
+    locale: Res<Locale>,
 ) {
-    commands.spawn((FailMarker, SpriteBundle {
-        texture: asset_server.load("BacklogBreakdown_GameOver.png"),
-        ..default()
-    }));
+    let level = current_level.get(&campaign);
+    let counts = &sim.consumed;
+    let mut lines = vec![locale.format("fail-total", &[("count", &counts.total)])];
+    for (item_type, category) in catalog.item_types().zip(&catalog.categories) {
+        let total = counts.of(item_type).map_or(0, |count| count.total);
+        lines.push(locale.format(
+            "fail-category",
+            &[("category", &category.name(&locale)), ("count", &total)],
+        ));
+    }
+    lines.push(locale.format("fail-survived", &[("time", &format_time(sim.elapsed))]));
+    lines.push(locale.format("fail-days", &[("days", &sim.calendar.days.len())]));
+    lines.push(locale.format("fail-seed", &[("seed", &seed.current)]));
+    match level {
+        Some(level) => {
+            lines.push(locale.format("fail-goal", &[("goal", &level.goal_label(&locale))]))
+        }
+        None => {
+            lines.push(locale.format(
+                "fail-difficulty",
+                &[("difficulty", &locale.get(difficulty.preset.label()))],
+            ));
+            lines.push(match scores.last_rank {
+                Some(rank) => locale.format("fail-rank", &[("rank", &(rank + 1))]),
+                // ended from the pause menu, so it was never up for a place
+                None if !sim.failed() => locale.get("fail-ended"),
+                None => locale.format("fail-unranked", &[("max", &HighScores::MAX_RUNS)]),
+            });
+        }
+    }
+    let stats = format!("\n{}", lines.join("\n"));
+    let heading = match level {
+        Some(level) => locale.format("fail-level-heading", &[("name", &level.title(&locale))]),
+        None => locale.get("fail-heading"),
+    };
+
+    commands.spawn((
+        FailMarker,
+        SpriteBundle {
+            texture: asset_server.load("BacklogBreakdown_GameOver.png"),
+            ..default()
+        },
+    ));
 
     commands
         .spawn((
@@ -44,9 +102,9 @@ fn spawn(
             children.spawn((
                 FailMarker,
                 TextBundle::from_section(
-                    "You've drowned in your backlog!",
+                    heading,
                     TextStyle {
-                        font: asset_server.load("chevyray_bird_seed.ttf"),
+                        font: locale.font.clone(),
                         font_size: 16.0,
                         color: Color::rgb(0.9, 0.9, 0.9),
                     },
@@ -56,16 +114,9 @@ fn spawn(
             children.spawn((
                 FailMarker,
                 TextBundle::from_section(
-                    format!(
-                        "\nTotal: {}\nBooks: {}\nMovies: {}\nGames: {}\nComics: {}",
-                        counts.total,
-                        counts.books.total,
-                        counts.movies.total,
-                        counts.games.total,
-                        counts.comics.total
-                    ),
+                    stats,
                     TextStyle {
-                        font: asset_server.load("chevyray_bird_seed.ttf"),
+                        font: locale.font.clone(),
                         font_size: 16.0,
                         color: Color::rgb(0.9, 0.9, 0.9),
                     },
@@ -88,51 +139,19 @@ fn spawn(
                     },
                 ))
                 .with_children(|children| {
-                    if let Some(index) = counts.books.favorite() {
-                        children.spawn((
-                            FailMarker,
-                            ImageBundle {
-                                image: UiImage {
-                                    texture: handles.books[index].queue_handle.clone(),
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                        ));
-                    }
-
-                    if let Some(index) = counts.movies.favorite() {
-                        children.spawn((
-                            FailMarker,
-                            ImageBundle {
-                                image: UiImage {
-                                    texture: handles.movies[index].queue_handle.clone(),
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                        ));
-                    }
-
-                    if let Some(index) = counts.games.favorite() {
-                        children.spawn((
-                            FailMarker,
-                            ImageBundle {
-                                image: UiImage {
-                                    texture: handles.games[index].queue_handle.clone(),
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                        ));
-                    }
-
-                    if let Some(index) = counts.comics.favorite() {
+                    for item_type in catalog.item_types() {
+                        let Some(variant) = counts.of(item_type).and_then(|c| c.favorite()) else {
+                            continue;
+                        };
+                        let cover = art
+                            .look(&catalog, &mut images, item_type, variant)
+                            .cover
+                            .clone();
                         children.spawn((
                             FailMarker,
                             ImageBundle {
                                 image: UiImage {
-                                    texture: handles.comics[index].queue_handle.clone(),
+                                    texture: cover,
                                     ..default()
                                 },
                                 ..default()
@@ -144,6 +163,7 @@ fn spawn(
             children
                 .spawn((
                     FailMarker,
+                    StartButton,
                     ButtonBundle {
                         style: Style {
                             width: Val::Px(200.0),
@@ -162,9 +182,9 @@ fn spawn(
                     parent.spawn((
                         FailMarker,
                         TextBundle::from_section(
-                            "Click to Replay",
+                            locale.get("fail-replay"),
                             TextStyle {
-                                font: asset_server.load("chevyray_bird_seed.ttf"),
+                                font: locale.font.clone(),
                                 font_size: 20.0,
                                 color: Color::rgb(0.9, 0.9, 0.9),
                             },
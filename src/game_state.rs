@@ -2,8 +2,28 @@ use bevy::prelude::*;
 
 #[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum GameState {
+    /// Waiting for the item catalog and the other data files.
     #[default]
+    Loading,
     StartScreen,
+    /// Picking a campaign level.
+    LevelSelect,
     Playing,
+    /// Goes straight back to [`GameState::Playing`] to start a fresh run.
+    Restarting,
     Failed,
+    /// The campaign level's goal was met.
+    Victory,
+    /// Browsing the achievements.
+    Achievements,
+}
+
+/// Whether the run in [`GameState::Playing`] is frozen behind the pause menu
+/// or the end of week summary.
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    WeekSummary,
 }
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::CurrentLevel,
+    catalog::ItemCatalog,
+    difficulty::{Difficulty, Preset},
+    game_state::GameState,
+    locale::Locale,
+    replay::Replay,
+    seed::RunSeed,
+    sim::Sim,
+    storage,
+};
+
+/// Keeps the best runs between launches.
+pub struct HighScoresPlugin;
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<HighScores>(HighScores::KEY).unwrap_or_default())
+            .add_systems(
+                // restarted and abandoned runs don't count
+                OnTransition {
+                    from: GameState::Playing,
+                    to: GameState::Failed,
+                },
+                HighScores::record_run,
+            );
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct HighScores {
+    /// Best run first.
+    pub runs: Vec<ScoreEntry>,
+    /// Place of the run that just ended, if it made the table.
+    #[serde(skip)]
+    pub last_rank: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScoreEntry {
+    pub total: u32,
+    /// Items consumed per category, by category id.
+    pub categories: Vec<(String, u32)>,
+    pub survived: Duration,
+    pub seed: u64,
+    #[serde(default)]
+    pub difficulty: Preset,
+    /// Unix time the run ended.
+    pub timestamp: u64,
+}
+
+impl HighScores {
+    pub const MAX_RUNS: usize = 10;
+    const KEY: &'static str = "high_scores";
+
+    /// Adds a run in order of total consumed, then survival time. Returns its
+    /// place if it made the table.
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        let rank = self
+            .runs
+            .iter()
+            .position(|run| (entry.total, entry.survived) > (run.total, run.survived))
+            .unwrap_or(self.runs.len());
+        if rank >= Self::MAX_RUNS {
+            return None;
+        }
+        self.runs.insert(rank, entry);
+        self.runs.truncate(Self::MAX_RUNS);
+        Some(rank)
+    }
+
+    fn record_run(
+        mut scores: ResMut<HighScores>,
+        sim: Res<Sim>,
+        catalog: Res<ItemCatalog>,
+        seed: Res<RunSeed>,
+        difficulty: Res<Difficulty>,
+        replay: Option<Res<Replay>>,
+        current_level: Res<CurrentLevel>,
+    ) {
+        // replays are runs that already happened, levels aren't endless runs,
+        // and a run ended from the pause menu wasn't lost
+        if replay.is_some() || current_level.0.is_some() || !sim.failed() {
+            scores.last_rank = None;
+            return;
+        }
+        let categories = catalog
+            .item_types()
+            .zip(&catalog.categories)
+            .map(|(item_type, category)| {
+                let total = sim.consumed.of(item_type).map_or(0, |count| count.total);
+                (category.id.clone(), total)
+            })
+            .collect();
+        scores.last_rank = scores.insert(ScoreEntry {
+            total: sim.consumed.total,
+            categories,
+            survived: sim.elapsed,
+            seed: seed.current,
+            difficulty: difficulty.preset,
+            timestamp: storage::now(),
+        });
+        storage::save(Self::KEY, &*scores);
+    }
+
+    /// The table as shown on the start screen.
+    pub fn table(&self, locale: &Locale) -> String {
+        let mut table = locale.get("scores-title");
+        if self.runs.is_empty() {
+            table.push('\n');
+            table.push_str(&locale.get("scores-empty"));
+            return table;
+        }
+        for (i, run) in self.runs.iter().enumerate() {
+            table.push_str(&format!(
+                "\n{:>2}. {:>3}  {}  {}",
+                i + 1,
+                run.total,
+                format_time(run.survived),
+                locale.get(run.difficulty.label())
+            ));
+        }
+        table
+    }
+}
+
+/// `m:ss`
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -1,20 +1,27 @@
-use std::time::Duration;
-
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{
+    ecs::system::EntityCommand,
+    prelude::*,
+    sprite::Anchor,
+    utils::{HashMap, HashSet},
+};
 use bevy_mod_picking::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    catalog::ItemCatalog,
     layers,
-    stack::{InStack, RemoveFromStack, StackOffset},
+    sim::{Item, ItemId, Location, Sim, SimInput, SimInputs},
+    stack::RemoveFromStack,
+    titles::ItemArt,
+    touch::is_touch,
 };
 
 #[derive(Bundle)]
 pub struct ItemBundle {
     sprite_bundle: SpriteBundle,
+    name: Name,
     item_type: ItemType,
-    item_index: ItemHandleIndex,
-    in_stack: InStack,
-    stack_offset: StackOffset,
+    item_id: ItemId,
     pickable_bundle: PickableBundle,
     on_drag_start: On<Pointer<DragStart>>,
     on_drag_end: On<Pointer<DragEnd>>,
@@ -22,42 +29,41 @@ pub struct ItemBundle {
 }
 
 impl ItemBundle {
-    pub fn new(
-        item_type: ItemType,
-        texture: Handle<Image>,
-        offset: f32,
-        item_index: usize,
-        stack_entity: Entity,
-    ) -> Self {
+    pub fn new(item: &Item, title: &str, texture: Handle<Image>) -> Self {
         Self {
             sprite_bundle: SpriteBundle {
                 sprite: Sprite {
                     anchor: Anchor::BottomCenter,
-                    // custom_size: Some(item_type.stack_dimensions()),
                     ..default()
                 },
                 texture,
                 transform: Transform::from_xyz(0., 0., layers::ITEMS),
                 ..default()
             },
-            item_type,
-            item_index: ItemHandleIndex(item_index),
-            in_stack: InStack(stack_entity),
-            stack_offset: StackOffset(offset),
+            name: Name::new(title.to_string()),
+            item_type: item.item_type,
+            item_id: item.id,
             pickable_bundle: PickableBundle::default(),
+            // touches pick items up with a long press instead
             on_drag_start: On::<Pointer<DragStart>>::commands_mut(|evt, commands| {
+                if is_touch(evt.pointer_id) {
+                    return;
+                }
                 commands
                     .entity(evt.target)
-                    .insert((Pickable::IGNORE, ItemDragging))
+                    .insert(Pickable::IGNORE)
                     .add(RemoveFromStack);
             }),
             on_drag_end: On::<Pointer<DragEnd>>::commands_mut(|evt, commands| {
-                commands
-                    .entity(evt.target)
-                    .insert(Pickable::default())
-                    .remove::<ItemDragging>();
+                if is_touch(evt.pointer_id) {
+                    return;
+                }
+                commands.entity(evt.target).add(ReleaseItem);
             }),
             on_drag: On::<Pointer<Drag>>::target_component_mut::<Transform>(|drag, transform| {
+                if is_touch(drag.pointer_id) {
+                    return;
+                }
                 transform.translation.x += drag.delta.x; // Make the square follow the mouse
                 transform.translation.y -= drag.delta.y;
                 transform.translation.z = layers::ITEMS + 1.;
@@ -66,124 +72,89 @@ impl ItemBundle {
     }
 }
 
-#[derive(Component, Clone, Copy)]
-pub struct ItemHandleIndex(pub usize);
-
-#[derive(Component, Default, Clone, Copy, PartialEq, Debug)]
-pub enum ItemType {
-    #[default]
-    Book,
-    Movie,
-    Game,
-    Comic,
-}
-
-impl ItemType {
-    pub fn stack_dimensions(&self) -> Vec2 {
-        match self {
-            ItemType::Book => Vec2::new(65., 17.),
-            ItemType::Movie => Vec2::new(65., 10.),
-            ItemType::Game => Vec2::new(65., 12.),
-            ItemType::Comic => Vec2::new(43., 8.),
-        }
-    }
-
-    pub fn label(&self) -> &'static str {
-        match self {
-            ItemType::Book => "Books",
-            ItemType::Movie => "Movies",
-            ItemType::Game => "Games",
-            ItemType::Comic => "Comics",
-        }
-    }
-
-    pub fn consume_time(&self) -> Duration {
-        match self {
-            ItemType::Book => Duration::from_secs_f32(5.),
-            ItemType::Movie => Duration::from_secs_f32(2.),
-            ItemType::Game => Duration::from_secs_f32(10.),
-            ItemType::Comic => Duration::from_secs_f32(1.),
-        }
+/// Let go of a dragged item. The sim puts it back on a stack if it wasn't dropped anywhere.
+pub struct ReleaseItem;
+impl EntityCommand for ReleaseItem {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(item) = world.get::<ItemId>(id).copied() else {
+            return;
+        };
+        world
+            .resource_mut::<SimInputs>()
+            .push(SimInput::Release(item));
     }
+}
 
-    pub fn get_stack_handle(&self, handles: &ItemHandles, index: usize) -> Handle<Image> {
-        match self {
-            ItemType::Book => handles.books[index].stack_handle.clone(),
-            ItemType::Movie => handles.movies[index].stack_handle.clone(),
-            ItemType::Game => handles.games[index].stack_handle.clone(),
-            ItemType::Comic => handles.comics[index].stack_handle.clone(),
+/// Sprite entity of each item in the [`Sim`].
+#[derive(Resource, Default)]
+pub struct ItemEntities(pub HashMap<ItemId, Entity>);
+
+/// Spawns sprites for new items, despawns consumed ones and swaps between the
+/// side and cover art depending on where the item is.
+pub fn mirror_items(
+    mut commands: Commands,
+    sim: Res<Sim>,
+    catalog: Res<ItemCatalog>,
+    mut art: ResMut<ItemArt>,
+    mut images: ResMut<Assets<Image>>,
+    mut entities: ResMut<ItemEntities>,
+    mut items: Query<(&mut Handle<Image>, &mut Sprite, &mut Pickable), With<ItemId>>,
+) {
+    let alive: HashSet<ItemId> = sim.items().map(|(item, _)| item.id).collect();
+    entities.0.retain(|id, e| {
+        if alive.contains(id) {
+            return true;
         }
-    }
-
-    pub fn get_queue_handle(&self, handles: &ItemHandles, index: usize) -> Handle<Image> {
-        match self {
-            ItemType::Book => handles.books[index].queue_handle.clone(),
-            ItemType::Movie => handles.movies[index].queue_handle.clone(),
-            ItemType::Game => handles.games[index].queue_handle.clone(),
-            ItemType::Comic => handles.comics[index].queue_handle.clone(),
+        commands.entity(*e).despawn();
+        false
+    });
+
+    for (item, location) in sim.items() {
+        let stacked = matches!(location, Location::Stack(_));
+        let look = art.look(&catalog, &mut images, item.item_type, item.variant);
+        let texture = if stacked {
+            look.side.clone()
+        } else {
+            look.cover.clone()
+        };
+
+        let Some(e) = entities.0.get(&item.id) else {
+            let e = commands
+                .spawn(ItemBundle::new(item, &look.title, texture))
+                .id();
+            entities.0.insert(item.id, e);
+            continue;
+        };
+        let Ok((mut handle, mut sprite, mut pickable)) = items.get_mut(*e) else {
+            continue;
+        };
+
+        if *handle != texture {
+            *handle = texture;
         }
-    }
-
-    pub fn get_handles_max(&self, handles: &ItemHandles) -> usize {
-        match self {
-            ItemType::Book => handles.books.len(),
-            ItemType::Movie => handles.movies.len(),
-            ItemType::Game => handles.games.len(),
-            ItemType::Comic => handles.comics.len(),
+        let anchor = if stacked {
+            Anchor::BottomCenter
+        } else {
+            Anchor::Center
+        };
+        if sprite.anchor.as_vec() != anchor.as_vec() {
+            sprite.anchor = anchor;
         }
-    }
-}
-
-#[derive(Resource)]
-pub struct ItemHandles {
-    pub books: Vec<ItemHandle>,
-    pub movies: Vec<ItemHandle>,
-    pub games: Vec<ItemHandle>,
-    pub comics: Vec<ItemHandle>,
-}
 
-impl ItemHandles {
-    pub fn load_handles(mut commands: Commands, asset_server: Res<AssetServer>) {
-        let mut books = vec![];
-        let mut movies = vec![];
-        let mut comics = vec![];
-        let mut games = vec![];
-
-        for i in 1..=25 {
-            books.push(ItemHandle {
-                stack_handle: asset_server.load(format!("Books/Book{i}_side.png")),
-                queue_handle: asset_server.load(format!("Books/Book{i}_cover.png")),
-            });
-
-            movies.push(ItemHandle {
-                stack_handle: asset_server.load(format!("Movies/Movie{i}_side.png")),
-                queue_handle: asset_server.load(format!("Movies/Movie{i}_cover.png")),
-            });
-
-            games.push(ItemHandle {
-                stack_handle: asset_server.load(format!("Games/Game{i}_side.png")),
-                queue_handle: asset_server.load(format!("Games/Game{i}_cover.png")),
-            });
-
-            comics.push(ItemHandle {
-                stack_handle: asset_server.load(format!("Comics/Comic{i}_side.png")),
-                queue_handle: asset_server.load(format!("Comics/Comic{i}_cover.png")),
-            });
+        let new_pickable = match location {
+            Location::Stack(_) => Pickable::default(),
+            // leave the dragged item alone so drop targets below it get the drop
+            Location::Held => continue,
+            Location::Queue(_) | Location::Active => Pickable::IGNORE,
+        };
+        if pickable.should_emit_events != new_pickable.should_emit_events
+            || pickable.should_block_lower != new_pickable.should_block_lower
+        {
+            *pickable = new_pickable;
         }
-
-        commands.insert_resource(ItemHandles {
-            books,
-            movies,
-            comics,
-            games,
-        });
     }
 }
 
-pub struct ItemHandle {
-    pub stack_handle: Handle<Image>,
-    pub queue_handle: Handle<Image>,
-}
-
-#[derive(Component)]
-pub struct ItemDragging;
+/// Category of an item, as an index into [`ItemCatalog::categories`].
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct ItemType(pub usize);
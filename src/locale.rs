@@ -0,0 +1,380 @@
+use std::fmt::Display;
+
+use ab_glyph::{Font as _, FontArc};
+use bevy::{
+    app::AppExit,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{game_state::GameState, settings::Settings};
+
+const LOCALES_PATH: &str = "locales/all.locales.ron";
+/// Font every language uses unless it needs characters this doesn't have.
+pub const PIXEL_FONT: &str = "chevyray_bird_seed.ttf";
+
+/// Loads the translations and keeps every player-facing string in the
+/// language picked in the settings. The game stays in [`GameState::Loading`]
+/// until they're ready.
+pub struct LocalePlugin;
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Locales>()
+            .init_asset_loader::<LocalesLoader>()
+            .add_systems(Startup, load_locales)
+            .add_systems(
+                Update,
+                wait_for_locales.run_if(
+                    in_state(GameState::Loading).and_then(resource_exists::<LocalesHandle>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    Locale::switch.run_if(
+                        resource_exists::<Locales>().and_then(resource_changed::<Settings>()),
+                    ),
+                    (Localized::update, Locale::refresh_fonts)
+                        .run_if(resource_exists_and_changed::<Locale>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Every language the game is translated to. The first one is the reference
+/// the others are checked against.
+#[derive(Asset, TypePath, Resource, Clone)]
+pub struct Locales {
+    pub languages: Vec<Locale>,
+}
+
+impl Locales {
+    /// The language with this code, or the reference one.
+    pub fn get(&self, code: &str) -> &Locale {
+        self.languages
+            .iter()
+            .find(|locale| locale.code == code)
+            .unwrap_or(&self.languages[0])
+    }
+
+    /// The language after or before `code`, wrapping around.
+    pub fn step(&self, code: &str, up: bool) -> &Locale {
+        let count = self.languages.len();
+        let i = self
+            .languages
+            .iter()
+            .position(|locale| locale.code == code)
+            .unwrap_or(0);
+        let i = if up { i + 1 } else { i + count - 1 } % count;
+        &self.languages[i]
+    }
+}
+
+/// The language the game is shown in.
+#[derive(Resource, Clone, Default)]
+pub struct Locale {
+    pub code: String,
+    /// Name of the language in itself.
+    pub name: String,
+    /// The pixel font, or the language's fallback font when the pixel font
+    /// can't show all of its strings.
+    pub font: Handle<Font>,
+    strings: HashMap<String, String>,
+    lists: HashMap<String, Vec<String>>,
+}
+
+impl Locale {
+    /// The string for `key`. Keys no language has show up as themselves so
+    /// they're easy to spot.
+    pub fn get(&self, key: &str) -> String {
+        self.strings
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// The string for `key`, or `default` if no language has it. For names
+    /// that come from data files, which don't have to be translated.
+    pub fn get_or(&self, key: &str, default: &str) -> String {
+        self.strings
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    /// The string for `key` with every `{name}` in it replaced by its argument.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key);
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    /// The list of strings for `key`.
+    pub fn list(&self, key: &str) -> &[String] {
+        self.lists.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    fn switch(
+        mut commands: Commands,
+        settings: Res<Settings>,
+        locales: Res<Locales>,
+        locale: Res<Locale>,
+    ) {
+        if locale.code != settings.language {
+            commands.insert_resource(locales.get(&settings.language).clone());
+        }
+    }
+
+    /// Moves text over to the new language's font.
+    fn refresh_fonts(locale: Res<Locale>, locales: Res<Locales>, mut texts: Query<&mut Text>) {
+        for mut text in &mut texts {
+            for section in &mut text.sections {
+                let localized = locales
+                    .languages
+                    .iter()
+                    .any(|other| other.font == section.style.font);
+                if localized && section.style.font != locale.font {
+                    section.style.font = locale.font.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Text that shows the string with this key, and follows the language setting.
+#[derive(Component)]
+pub struct Localized(pub &'static str);
+
+impl Localized {
+    fn update(locale: Res<Locale>, mut texts: Query<(&mut Text, &Localized)>) {
+        for (mut text, localized) in &mut texts {
+            text.sections[0].value = locale.get(localized.0);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct LocalesHandle(Handle<Locales>);
+
+fn load_locales(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LocalesHandle(asset_server.load(LOCALES_PATH)));
+}
+
+fn wait_for_locales(
+    mut commands: Commands,
+    handle: Res<LocalesHandle>,
+    asset_server: Res<AssetServer>,
+    all_locales: Res<Assets<Locales>>,
+    settings: Res<Settings>,
+    mut exit: EventWriter<AppExit>,
+) {
+    match asset_server.get_load_state(&handle.0) {
+        Some(LoadState::Loaded) => {
+            let Some(locales) = all_locales.get(&handle.0) else {
+                return;
+            };
+            commands.insert_resource(locales.get(&settings.language).clone());
+            commands.insert_resource(locales.clone());
+            commands.remove_resource::<LocalesHandle>();
+        }
+        Some(LoadState::Failed) => {
+            error!("could not load {LOCALES_PATH}, see the asset error above");
+            exit.send(AppExit);
+        }
+        _ => {}
+    }
+}
+
+#[derive(Deserialize)]
+struct LocalesFile {
+    /// Paths of the language files. The first one is the reference.
+    languages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct LocaleFile {
+    code: String,
+    name: String,
+    /// Font for languages with characters the pixel font doesn't have.
+    #[serde(default)]
+    fallback_font: Option<String>,
+    #[serde(default)]
+    strings: HashMap<String, String>,
+    #[serde(default)]
+    lists: HashMap<String, Vec<String>>,
+}
+
+impl LocaleFile {
+    fn texts(&self) -> impl Iterator<Item = &String> {
+        self.strings
+            .values()
+            .chain(self.lists.values().flatten())
+            .chain([&self.name])
+    }
+
+    /// Characters of the strings `font` can't show.
+    fn missing_glyphs(&self, font: &FontArc) -> String {
+        let mut missing: Vec<char> = self
+            .texts()
+            .flat_map(|text| text.chars())
+            .filter(|c| !c.is_whitespace() && font.glyph_id(*c).0 == 0)
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing.into_iter().collect()
+    }
+
+    /// Fills in what the reference has and this doesn't, so the game never
+    /// shows a bare key.
+    fn fill_from(&mut self, reference: &LocaleFile) {
+        let missing: Vec<&str> = reference
+            .strings
+            .keys()
+            .chain(reference.lists.keys())
+            .filter(|key| !self.strings.contains_key(*key) && !self.lists.contains_key(*key))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            warn!(
+                "{} is missing {}, showing them in {}",
+                self.name,
+                missing.join(", "),
+                reference.name
+            );
+        }
+        for (key, value) in &reference.strings {
+            self.strings
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        for (key, value) in &reference.lists {
+            self.lists
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LocalesLoaderError {
+    #[error("could not read the language list: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the language list: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("could not read language {path}: {reason}")]
+    Language { path: String, reason: String },
+    #[error("could not load font {path}: {reason}")]
+    Font { path: String, reason: String },
+    #[error("no languages are listed")]
+    Empty,
+    #[error("language {0} is listed more than once")]
+    DuplicateLanguage(String),
+    #[error("{font} can't show {chars} from language {code}, give it a fallback_font that can")]
+    MissingGlyphs {
+        code: String,
+        font: String,
+        chars: String,
+    },
+}
+
+/// Loads `*.locales.ron` files along with every language file they list, and
+/// makes sure each language has a font that can show all of its strings.
+#[derive(Default)]
+pub struct LocalesLoader;
+
+impl AssetLoader for LocalesLoader {
+    type Asset = Locales;
+    type Settings = ();
+    type Error = LocalesLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Locales, LocalesLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: LocalesFile = ron::de::from_bytes(&bytes)?;
+            if file.languages.is_empty() {
+                return Err(LocalesLoaderError::Empty);
+            }
+            let pixel_font = load_font(load_context, PIXEL_FONT).await?;
+
+            let mut files: Vec<LocaleFile> = Vec::new();
+            for path in file.languages {
+                let to_error = |reason: String| LocalesLoaderError::Language {
+                    path: path.clone(),
+                    reason,
+                };
+                let bytes = load_context
+                    .read_asset_bytes(path.clone())
+                    .await
+                    .map_err(|e| to_error(e.to_string()))?;
+                let mut locale: LocaleFile =
+                    ron::de::from_bytes(&bytes).map_err(|e| to_error(e.to_string()))?;
+                if files.iter().any(|l| l.code == locale.code) {
+                    return Err(LocalesLoaderError::DuplicateLanguage(locale.code));
+                }
+                if let Some(reference) = files.first() {
+                    locale.fill_from(reference);
+                }
+                files.push(locale);
+            }
+
+            let mut languages = Vec::new();
+            for locale in files {
+                let mut font_path = PIXEL_FONT.to_string();
+                let mut missing = locale.missing_glyphs(&pixel_font);
+                if let (false, Some(fallback)) = (missing.is_empty(), &locale.fallback_font) {
+                    font_path = fallback.clone();
+                    missing = locale.missing_glyphs(&load_font(load_context, fallback).await?);
+                }
+                if !missing.is_empty() {
+                    return Err(LocalesLoaderError::MissingGlyphs {
+                        code: locale.code,
+                        font: font_path,
+                        chars: missing,
+                    });
+                }
+                languages.push(Locale {
+                    code: locale.code,
+                    name: locale.name,
+                    font: load_context.load(font_path),
+                    strings: locale.strings,
+                    lists: locale.lists,
+                });
+            }
+
+            Ok(Locales { languages })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["locales.ron"]
+    }
+}
+
+async fn load_font<'a>(
+    load_context: &mut LoadContext<'a>,
+    path: &str,
+) -> Result<FontArc, LocalesLoaderError> {
+    let to_error = |reason: String| LocalesLoaderError::Font {
+        path: path.to_string(),
+        reason,
+    };
+    let font = load_context
+        .load_direct(path.to_string())
+        .await
+        .map_err(|e| to_error(e.to_string()))?
+        .take::<Font>()
+        .ok_or_else(|| to_error("not a font".to_string()))?;
+    Ok(font.font)
+}
@@ -4,46 +4,104 @@
 // Feel free to delete this line.
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+mod accessibility;
+mod achievements;
+#[cfg(not(target_arch = "wasm32"))]
+mod bot;
+mod calendar;
+mod campaign;
+mod catalog;
 mod consume_counter;
+mod cursor;
+mod curve;
+#[cfg(feature = "debug_console")]
+mod debug_console;
 mod dialog;
+mod difficulty;
+mod event_log;
 mod fail_screen;
 mod game_state;
+mod high_scores;
 mod item;
+mod launch;
 mod layers;
+mod locale;
+mod pause_menu;
+mod presets;
 mod queue;
+mod replay;
+mod saved_run;
+mod seed;
+mod settings;
+mod sim;
 mod spawning;
 mod stack;
 mod start_screen;
+mod storage;
 mod stress;
+#[cfg(test)]
+mod tests;
+mod titles;
+mod touch;
 
 use crate::queue::{in_queue_transforms, Queue};
-use bevy::window::WindowResolution;
+use accessibility::{AccessibilityPlugin, Background};
+use achievements::{AchievementProgress, AchievementsPlugin};
+use bevy::audio::PlaybackMode;
+use bevy::render::camera::ScalingMode;
 use bevy::{asset::AssetMetaCheck, prelude::*};
 use bevy_mod_picking::prelude::*;
 use bevy_rand::prelude::*;
-use consume_counter::{ConsumeCount, CounterMarker};
-use dialog::ShownDialog;
+use calendar::{CalendarPlugin, DayLabel, WeekSummary};
+use campaign::{check_goal, show_cue_dialogs, Campaign, CampaignPlugin, CurrentLevel, GoalText};
+use catalog::{CatalogPlugin, ItemCatalog};
+use consume_counter::CounterMarker;
+use cursor::CursorPlugin;
+use curve::{CurvePlugin, DifficultyCurve};
+use dialog::{DialogDismissed, ShownDialog};
+use difficulty::{Difficulty, DifficultyPlugin};
+use event_log::{EventLog, EventLogPlugin};
 use fail_screen::FailScreenPlugin;
-use game_state::GameState;
-use item::{ItemHandles, ItemType};
-use queue::{check_active, consume_active, draw_timer};
-use spawning::{check_timer, draw_button, spawn_button, SpawningPlugin};
-use stack::{check_stack, restack, stack_items, Stack, StackPenalty};
+use game_state::{GameState, PauseState};
+use high_scores::HighScoresPlugin;
+use item::{mirror_items, ItemEntities, ItemType};
+use locale::{Locale, LocalePlugin};
+use pause_menu::PauseMenuPlugin;
+use presets::{Presets, PresetsPlugin};
+use queue::draw_timer;
+use replay::{Recorder, Replay, ReplayPlugin, StepDelta};
+use saved_run::{SavedRun, SavedRunPlugin};
+use seed::{RunSeed, SeedPlugin};
+use settings::{AudioChannel, Settings, SettingsPlugin, VIEW_SIZE};
+use sim::{PlayerInput, Sim, SimEvent, SimInputs};
+use spawning::{draw_button, spawn_button};
+use stack::{check_stack, stack_items, SaleIcon, Stack, StackLabel};
 use start_screen::StartScreenPlugin;
-use stress::{fail_state, StressMeter, StressText};
+use stress::{fail_state, StressMeter, StressPopupText, StressText};
+use titles::ItemArt;
+use touch::TouchPlugin;
 
 fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(games) = launch::option("bot") {
+        bot::run(&games);
+        return;
+    }
+
+    let settings = Settings::load();
     App::new()
-        .add_state::<GameState>()
-        .insert_resource(StackPenalty(0.))
         .insert_resource(AssetMetaCheck::Never)
-        .insert_resource(ConsumeCount::default())
         .insert_resource(Msaa::Off)
+        .insert_resource(settings.clone())
         .add_plugins((
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        resolution: WindowResolution::new(640., 360.),
+                        resolution: settings.window_resolution(),
+                        mode: settings.window_mode(),
+                        // on the web the canvas follows the page, so phones can
+                        // play in either orientation
+                        fit_canvas_to_parent: true,
                         ..default()
                     }),
                     ..default()
@@ -53,56 +111,138 @@ fn main() {
                 .build()
                 .disable::<DebugPickingPlugin>(),
             EntropyPlugin::<ChaCha8Rng>::default(),
-            SpawningPlugin,
+            GamePlugin,
         ))
-        .add_systems(Startup, (spawn_camera, ItemHandles::load_handles))
-        .add_plugins((StartScreenPlugin, FailScreenPlugin))
-        .add_systems(
-            OnEnter(GameState::Playing),
-            ((
-                setup,
-                StressMeter::spawn,
-                spawn_button,
-                CounterMarker::spawn,
-                ShownDialog::spawn,
-                BackgroundMusic::spawn,
-                Queue::spawn,
-            ),)
-                .chain(),
-        )
-        .add_systems(
-            Update,
-            (
-                in_queue_transforms,
-                stack_items,
-                restack,
-                check_active,
-                consume_active,
-                draw_timer,
-                StressMeter::animate_meter,
-                StressMeter::animate_stress_overlays,
-                fail_state,
-                check_stack,
-                CounterMarker::update_counter,
-                ShownDialog::handle_visibility,
-                StressText::animate_text,
-                skip_to_end,
-                (check_timer, draw_button).chain(),
-            )
-                .run_if(in_state(GameState::Playing)),
-        )
-        .add_systems(
-            OnExit(GameState::Playing),
-            (
-                despawn_playing,
-                CounterMarker::despawn,
-                ShownDialog::despawn,
-                BackgroundMusic::despawn,
-            ),
-        )
         .run();
 }
 
+/// The game on top of the engine plugins, which [`main`] adds for a window and
+/// the tests swap for headless ones.
+pub struct GamePlugin;
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .add_event::<SimEvent>()
+            .add_event::<DialogDismissed>()
+            .init_resource::<SimInputs>()
+            .add_systems(Startup, spawn_camera)
+            .add_systems(
+                Update,
+                finish_loading.run_if(
+                    in_state(GameState::Loading)
+                        .and_then(resource_exists::<ItemCatalog>())
+                        .and_then(resource_exists::<DifficultyCurve>())
+                        .and_then(resource_exists::<Presets>())
+                        .and_then(resource_exists::<Campaign>())
+                        .and_then(resource_exists::<Locale>()),
+                ),
+            )
+            .add_plugins((
+                CatalogPlugin,
+                CurvePlugin,
+                SettingsPlugin,
+                DifficultyPlugin,
+                SeedPlugin,
+                ReplayPlugin,
+                HighScoresPlugin,
+                PauseMenuPlugin,
+                StartScreenPlugin,
+                FailScreenPlugin,
+                CalendarPlugin,
+                CampaignPlugin,
+                CursorPlugin,
+                TouchPlugin,
+                AccessibilityPlugin,
+            ))
+            .add_plugins((
+                LocalePlugin,
+                AchievementsPlugin,
+                EventLogPlugin,
+                SavedRunPlugin,
+                PresetsPlugin,
+            ))
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    RunSeed::reseed,
+                    (
+                        setup,
+                        StressMeter::spawn,
+                        spawn_button,
+                        CounterMarker::spawn,
+                        ShownDialog::spawn,
+                        BackgroundMusic::spawn,
+                        Queue::spawn,
+                        Recorder::start.run_if(resource_exists::<Recorder>()),
+                    ),
+                    apply_deferred,
+                    SavedRun::resume,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    // the player's inputs of this frame land before they're recorded
+                    apply_deferred.after(PlayerInput),
+                    StepDelta::from_time,
+                    Replay::feed.run_if(resource_exists::<Replay>()),
+                    Recorder::record.run_if(resource_exists::<Recorder>()),
+                    ShownDialog::dismiss,
+                    step_sim,
+                    EventLog::write.run_if(resource_exists::<EventLog>()),
+                    AchievementProgress::track.run_if(not(resource_exists::<Replay>())),
+                    ItemArt::forget_consumed,
+                    (
+                        mirror_items,
+                        in_queue_transforms,
+                        stack_items,
+                        draw_timer,
+                        StressMeter::animate_meter,
+                        StressMeter::animate_stress_overlays,
+                        fail_state,
+                        check_goal,
+                        check_stack,
+                        CounterMarker::update_counter,
+                        ShownDialog::show_bulk_purchase,
+                        ShownDialog::show_sales,
+                        show_cue_dialogs,
+                        SaleIcon::update,
+                        DayLabel::update,
+                        GoalText::update,
+                        WeekSummary::open,
+                        StressPopupText::spawn_popups,
+                        play_sfx,
+                        draw_button,
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and_then(in_state(PauseState::Running))),
+            )
+            .add_systems(
+                Update,
+                (
+                    ShownDialog::handle_visibility,
+                    StressText::animate_text,
+                    StackLabel::update.run_if(resource_changed::<Locale>()),
+                )
+                    .run_if(in_state(GameState::Playing).and_then(in_state(PauseState::Running))),
+            )
+            .add_systems(
+                OnExit(GameState::Playing),
+                (
+                    despawn_playing,
+                    CounterMarker::despawn,
+                    ShownDialog::despawn,
+                    BackgroundMusic::despawn,
+                ),
+            );
+
+        #[cfg(feature = "debug_console")]
+        app.add_plugins(debug_console::DebugConsolePlugin);
+    }
+}
+
 #[derive(Resource)]
 pub struct Sfx {
     no_click: Handle<AudioSource>,
@@ -112,7 +252,17 @@ pub struct Sfx {
 }
 
 fn spawn_camera(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(Camera2dBundle::default());
+    commands.spawn(Camera2dBundle {
+        projection: OrthographicProjection {
+            // shows the same area at any window size
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: VIEW_SIZE.x,
+                min_height: VIEW_SIZE.y,
+            },
+            ..default()
+        },
+        ..default()
+    });
 
     commands.insert_resource(Sfx {
         no_click: asset_server.load("sfx/no-click.ogg"),
@@ -122,13 +272,26 @@ fn spawn_camera(mut commands: Commands, asset_server: Res<AssetServer>) {
     });
 }
 
+/// Leaves [`GameState::Loading`] once every data file is in.
+fn finish_loading(mut state: ResMut<NextState<GameState>>) {
+    state.set(GameState::StartScreen);
+}
+
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    catalog: Res<ItemCatalog>,
+    difficulty: Res<Difficulty>,
+    presets: Res<Presets>,
+    curve: Res<DifficultyCurve>,
+    campaign: Res<Campaign>,
+    current_level: Res<CurrentLevel>,
+    replay: Option<Res<Replay>>,
     mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
 ) {
-    commands.insert_resource(ConsumeCount::default());
     commands.spawn((
+        Background,
         SpriteBundle {
             texture: asset_server.load("background.png"),
             ..default()
@@ -136,20 +299,77 @@ fn setup(
         Pickable::IGNORE,
     ));
 
-    Stack::spawn_stacks(&mut commands, &asset_server, &mut rng);
+    let balance = replay.map_or_else(
+        || current_level.balance(&campaign, &difficulty, &presets),
+        |replay| replay.balance(),
+    );
+    let (categories, level) = match current_level.get(&campaign) {
+        Some(level) => {
+            let (categories, rules) = level
+                .rules(&catalog)
+                .expect("levels are checked against the catalog when loading");
+            (categories, Some(rules))
+        }
+        None => (catalog.rules(), None),
+    };
+    let sim = Sim::new(categories, balance, curve.clone(), level, &mut *rng);
+    Stack::spawn_stacks(&mut commands, &asset_server, &locale, &sim, &catalog);
+    commands.insert_resource(sim);
+    commands.insert_resource(SimInputs::default());
+    commands.insert_resource(ItemEntities::default());
+    commands.insert_resource(ItemArt::default());
+}
+
+/// Advances the rules by a frame with everything the player did since the last step.
+fn step_sim(
+    mut sim: ResMut<Sim>,
+    mut inputs: ResMut<SimInputs>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    delta: Res<StepDelta>,
+    mut events: EventWriter<SimEvent>,
+) {
+    let inputs = std::mem::take(&mut inputs.0);
+    events.send_batch(sim.step(delta.0, &inputs, &mut *rng));
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut events: EventReader<SimEvent>,
+    sfx: Res<Sfx>,
+    settings: Res<Settings>,
+) {
+    for event in events.read() {
+        let (source, volume) = match event {
+            SimEvent::Bought => (&sfx.buy, 1.),
+            SimEvent::Queued { .. } => (&sfx.queue, 0.9),
+            SimEvent::Consumed { .. } => (&sfx.consume, 1.),
+            SimEvent::DayEnded { bought: false, .. } => (&sfx.no_click, 0.5),
+            _ => continue,
+        };
+        commands.spawn(settings.sound(
+            source.clone(),
+            PlaybackMode::Despawn,
+            AudioChannel::Sfx,
+            volume,
+        ));
+    }
 }
 
 fn despawn_playing(
     mut commands: Commands,
     q: Query<
         Entity,
-        Or<(
-            With<ItemType>,
-            With<Sprite>,
-            With<Stack>,
-            With<StressMeter>,
-            With<Text>,
-        )>,
+        (
+            Or<(
+                With<ItemType>,
+                With<Sprite>,
+                With<Stack>,
+                With<StressMeter>,
+                With<Text>,
+            )>,
+            // menus drawn over the run clean up after themselves
+            Without<Node>,
+        ),
     >,
 ) {
     for e in &q {
@@ -161,13 +381,15 @@ fn despawn_playing(
 struct BackgroundMusic;
 
 impl BackgroundMusic {
-    fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    fn spawn(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
         commands.spawn((
             BackgroundMusic,
-            AudioBundle {
-                source: asset_server.load("carefully-does-it.ogg"),
-                settings: PlaybackSettings::LOOP,
-            },
+            settings.sound(
+                asset_server.load("carefully-does-it.ogg"),
+                PlaybackMode::Loop,
+                AudioChannel::Music,
+                1.,
+            ),
         ));
     }
 
@@ -177,22 +399,3 @@ impl BackgroundMusic {
         }
     }
 }
-
-fn skip_to_end(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut state: ResMut<NextState<GameState>>,
-    button_inputs: Res<Input<GamepadButton>>,
-    gamepads: Res<Gamepads>,
-) {
-    if keyboard_input.pressed(KeyCode::Escape) || keyboard_input.pressed(KeyCode::Return) {
-        state.set(GameState::Failed);
-    }
-
-    for gamepad in gamepads.iter() {
-        if button_inputs.pressed(GamepadButton::new(gamepad, GamepadButtonType::Start))
-            || button_inputs.pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
-        {
-            state.set(GameState::Failed);
-        }
-    }
-}
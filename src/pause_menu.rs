@@ -0,0 +1,210 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{
+    game_state::{GameState, PauseState},
+    layers,
+    locale::{Locale, Localized},
+    saved_run::SaveRun,
+    settings::SettingsState,
+};
+
+/// Escape or gamepad Start freezes the run and opens a menu to resume,
+/// restart, change settings, end the run, save it for later or quit to the
+/// title screen.
+pub struct PauseMenuPlugin;
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .add_systems(
+                Update,
+                toggle_pause
+                    .run_if(in_state(GameState::Playing).and_then(in_state(SettingsState::Closed))),
+            )
+            .add_systems(
+                Update,
+                PauseButton::handle_clicks
+                    .run_if(in_state(PauseState::Paused).and_then(in_state(SettingsState::Closed))),
+            )
+            .add_systems(OnEnter(PauseState::Paused), (spawn_menu, pause_audio))
+            .add_systems(OnExit(PauseState::Paused), (despawn_menu, resume_audio))
+            .add_systems(OnExit(GameState::Playing), unpause)
+            .add_systems(OnEnter(GameState::Restarting), restart);
+    }
+}
+
+#[derive(Component)]
+struct PauseMenuMarker;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    EndRun,
+    SaveAndQuit,
+    QuitToTitle,
+}
+
+impl PauseButton {
+    const ALL: [PauseButton; 6] = [
+        PauseButton::Resume,
+        PauseButton::Restart,
+        PauseButton::Settings,
+        PauseButton::EndRun,
+        PauseButton::SaveAndQuit,
+        PauseButton::QuitToTitle,
+    ];
+
+    /// Key of the button's text.
+    fn label(self) -> &'static str {
+        match self {
+            PauseButton::Resume => "pause-resume",
+            PauseButton::Restart => "pause-restart",
+            PauseButton::Settings => "pause-settings",
+            PauseButton::EndRun => "pause-end-run",
+            PauseButton::SaveAndQuit => "pause-save-quit",
+            PauseButton::QuitToTitle => "pause-quit",
+        }
+    }
+
+    fn handle_clicks(
+        q: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+        mut commands: Commands,
+        mut pause: ResMut<NextState<PauseState>>,
+        mut state: ResMut<NextState<GameState>>,
+        mut settings: ResMut<NextState<SettingsState>>,
+    ) {
+        for (interaction, button) in &q {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            match button {
+                PauseButton::Resume => pause.set(PauseState::Running),
+                PauseButton::Restart => state.set(GameState::Restarting),
+                PauseButton::Settings => settings.set(SettingsState::Open),
+                PauseButton::EndRun => state.set(GameState::Failed),
+                PauseButton::SaveAndQuit => {
+                    commands.add(SaveRun);
+                    state.set(GameState::StartScreen);
+                }
+                PauseButton::QuitToTitle => state.set(GameState::StartScreen),
+            }
+        }
+    }
+}
+
+fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    button_inputs: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    current: Res<State<PauseState>>,
+    mut pause: ResMut<NextState<PauseState>>,
+) {
+    let pressed = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepads.iter().any(|gamepad| {
+            button_inputs.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start))
+        });
+    if !pressed {
+        return;
+    }
+    pause.set(match current.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+        // the summary is closed by its own controls
+        PauseState::WeekSummary => return,
+    });
+}
+
+fn unpause(mut pause: ResMut<NextState<PauseState>>) {
+    pause.set(PauseState::Running);
+}
+
+fn restart(mut state: ResMut<NextState<GameState>>) {
+    state.set(GameState::Playing);
+}
+
+fn pause_audio(sinks: Query<&AudioSink>) {
+    for sink in &sinks {
+        sink.pause();
+    }
+}
+
+fn resume_audio(sinks: Query<&AudioSink>) {
+    for sink in &sinks {
+        sink.play();
+    }
+}
+
+fn spawn_menu(mut commands: Commands, locale: Res<Locale>) {
+    let font = locale.font.clone();
+    commands
+        .spawn((
+            PauseMenuMarker,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: Color::BLACK.with_a(0.6).into(),
+                // keeps clicks off the items underneath
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(layers::UI as i32),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Localized("pause-title"),
+                TextBundle::from_section(
+                    locale.get("pause-title"),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 24.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+
+            for button in PauseButton::ALL {
+                children
+                    .spawn((
+                        button,
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(160.),
+                                height: Val::Px(30.),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Localized(button.label()),
+                            TextBundle::from_section(
+                                locale.get(button.label()),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 16.,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                },
+                            ),
+                        ));
+                    });
+            }
+        });
+}
+
+fn despawn_menu(mut commands: Commands, q: Query<Entity, With<PauseMenuMarker>>) {
+    for e in &q {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,305 @@
+use std::time::Duration;
+
+use bevy::{
+    app::AppExit,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    campaign::{Campaign, CurrentLevel},
+    dialog::DialogBox,
+    difficulty::Difficulty,
+    game_state::GameState,
+    layers,
+    replay::{Recorder, Replay},
+    sim::{Balance, InvalidBalance, Sim},
+    stack::Stack,
+};
+
+pub const PRESETS_PATH: &str = "presets.balance.ron";
+
+/// Loads the numbers behind the difficulty presets.
+///
+/// Built with the `hot_reload` feature, saving the file while the game runs
+/// swaps the new numbers in, including for the run being played unless it's
+/// being recorded or replayed. A toast
+/// confirms the reload, and a file that doesn't parse shows its error on top
+/// of the game until it's fixed, while the last good numbers stay in use.
+pub struct PresetsPlugin;
+impl Plugin for PresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<PresetsFile>()
+            .init_asset_loader::<PresetsLoader>()
+            .add_systems(Startup, load_presets)
+            .add_systems(
+                Update,
+                wait_for_presets.run_if(
+                    in_state(GameState::Loading).and_then(not(resource_exists::<Presets>())),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    Presets::reload.run_if(resource_exists::<Presets>()),
+                    ReloadToast::fade,
+                ),
+            );
+    }
+}
+
+/// The [`Balance`] of each preset but [`Custom`](crate::difficulty::Preset::Custom),
+/// and pacing that's the same on every difficulty.
+#[derive(Resource, Deserialize, Clone, Copy, Debug)]
+pub struct Presets {
+    pub easy: Balance,
+    pub normal: Balance,
+    pub hard: Balance,
+    /// Seconds a dialog stays up unless it's clicked away.
+    pub dialog_time: f32,
+}
+
+impl Presets {
+    /// Reads a `*.balance.ron` file and checks the numbers make sense.
+    pub fn parse(bytes: &[u8]) -> Result<Presets, PresetsError> {
+        let presets: Presets = ron::de::from_bytes(bytes)?;
+
+        for (name, balance) in [
+            ("easy", presets.easy),
+            ("normal", presets.normal),
+            ("hard", presets.hard),
+        ] {
+            balance
+                .validate()
+                .map_err(|e| PresetsError::Invalid(name, e))?;
+        }
+        if !presets.dialog_time.is_finite() || presets.dialog_time <= 0. {
+            return Err(PresetsError::DialogTime);
+        }
+
+        Ok(presets)
+    }
+
+    /// `balance` if its numbers make sense, otherwise the normal preset.
+    /// `source` says where the bad numbers came from in the warning.
+    pub fn or_normal(&self, balance: Balance, source: &str) -> Balance {
+        match balance.validate() {
+            Ok(()) => balance,
+            Err(e) => {
+                warn!("{source}: {e}, playing on normal instead");
+                self.normal
+            }
+        }
+    }
+
+    /// Takes in an edited file. The run being played switches to the new
+    /// numbers unless it's a replay, which keeps the recorded ones, or being
+    /// recorded, which would then replay differently.
+    fn reload(
+        mut commands: Commands,
+        mut events: EventReader<AssetEvent<PresetsFile>>,
+        handle: Res<PresetsHandle>,
+        files: Res<Assets<PresetsFile>>,
+        mut presets: ResMut<Presets>,
+        sim: Option<ResMut<Sim>>,
+        replay: Option<Res<Replay>>,
+        recorder: Option<Res<Recorder>>,
+        mut stacks: Query<&mut Sprite, With<Stack>>,
+        mut dialog_box: Query<&mut DialogBox>,
+        difficulty: Res<Difficulty>,
+        campaign: Res<Campaign>,
+        current_level: Res<CurrentLevel>,
+        errors: Query<Entity, With<PresetsErrorText>>,
+    ) {
+        let modified = events.read().any(|event| event.is_modified(handle.0.id()));
+        let Some(file) = files.get(&handle.0).filter(|_| modified) else {
+            return;
+        };
+        for e in &errors {
+            commands.entity(e).despawn_recursive();
+        }
+        match &file.0 {
+            Ok(reloaded) => {
+                *presets = *reloaded;
+                match (sim, replay, recorder) {
+                    (Some(mut sim), None, None) => {
+                        sim.balance = current_level.balance(&campaign, &difficulty, &presets);
+                        for mut sprite in &mut stacks {
+                            if let Some(size) = &mut sprite.custom_size {
+                                size.y = sim.balance.max_stack_height;
+                            }
+                        }
+                    }
+                    (Some(_), None, Some(_)) => {
+                        info!("the run is being recorded, the new numbers apply from the next run");
+                    }
+                    _ => {}
+                }
+                for mut dialog_box in &mut dialog_box {
+                    let time = Duration::from_secs_f32(presets.dialog_time);
+                    dialog_box.timer.set_duration(time);
+                }
+                info!("reloaded {PRESETS_PATH}");
+                commands.spawn(ReloadToast::bundle());
+            }
+            Err(e) => {
+                error!("could not reload {PRESETS_PATH}: {e}");
+                commands.spawn(PresetsErrorText::bundle(e));
+            }
+        }
+    }
+}
+
+/// A read of the presets file. A file that doesn't parse still loads, holding
+/// the error, so an edit gone wrong can be shown in the game.
+#[derive(Asset, TypePath)]
+pub struct PresetsFile(Result<Presets, PresetsError>);
+
+#[derive(Resource)]
+struct PresetsHandle(Handle<PresetsFile>);
+
+fn load_presets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(PresetsHandle(asset_server.load(PRESETS_PATH)));
+}
+
+fn wait_for_presets(
+    mut commands: Commands,
+    handle: Res<PresetsHandle>,
+    asset_server: Res<AssetServer>,
+    files: Res<Assets<PresetsFile>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    match asset_server.get_load_state(&handle.0) {
+        Some(LoadState::Loaded) => match files.get(&handle.0).map(|file| &file.0) {
+            Some(Ok(presets)) => commands.insert_resource(*presets),
+            Some(Err(e)) => {
+                error!("could not load {PRESETS_PATH}: {e}");
+                exit.send(AppExit);
+            }
+            None => {}
+        },
+        Some(LoadState::Failed) => {
+            error!("could not load {PRESETS_PATH}, see the asset error above");
+            exit.send(AppExit);
+        }
+        _ => {}
+    }
+}
+
+/// Confirms the presets were reloaded, then fades out.
+#[derive(Component)]
+struct ReloadToast {
+    timer: Timer,
+}
+
+impl ReloadToast {
+    fn bundle() -> impl Bundle {
+        let mut text = TextBundle::from_section(
+            format!("Reloaded {PRESETS_PATH}"),
+            TextStyle {
+                font_size: 12.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(4.),
+            bottom: Val::Px(4.),
+            padding: UiRect::all(Val::Px(4.)),
+            ..default()
+        })
+        .with_background_color(Color::rgba_u8(102, 45, 60, 230));
+        text.z_index = ZIndex::Global(layers::UI as i32 + 100);
+        (
+            ReloadToast {
+                timer: Timer::from_seconds(2., TimerMode::Once),
+            },
+            text,
+        )
+    }
+
+    fn fade(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut toasts: Query<(Entity, &mut ReloadToast, &mut Text, &mut BackgroundColor)>,
+    ) {
+        for (e, mut toast, mut text, mut background) in &mut toasts {
+            if toast.timer.tick(time.delta()).finished() {
+                commands.entity(e).despawn_recursive();
+                continue;
+            }
+            let alpha = toast.timer.remaining_secs().min(0.5) * 2.;
+            text.sections[0].style.color.set_a(alpha);
+            background.0.set_a(alpha * 0.9);
+        }
+    }
+}
+
+/// Why the presets file didn't load, until a good one does.
+#[derive(Component)]
+struct PresetsErrorText;
+
+impl PresetsErrorText {
+    fn bundle(error: &PresetsError) -> impl Bundle {
+        let mut text = TextBundle::from_section(
+            format!("{PRESETS_PATH} was not reloaded\n{error}"),
+            TextStyle {
+                font_size: 12.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(4.),
+            right: Val::Px(4.),
+            top: Val::Px(4.),
+            padding: UiRect::all(Val::Px(4.)),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.6, 0., 0., 0.9));
+        text.z_index = ZIndex::Global(layers::UI as i32 + 100);
+        (PresetsErrorText, text)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PresetsError {
+    #[error("could not parse the presets: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("the {0} preset's {1}")]
+    Invalid(&'static str, InvalidBalance),
+    #[error("dialog_time has to be a number of seconds above zero")]
+    DialogTime,
+}
+
+/// Loads `*.balance.ron` files.
+#[derive(Default)]
+pub struct PresetsLoader;
+
+impl AssetLoader for PresetsLoader {
+    type Asset = PresetsFile;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<PresetsFile, std::io::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(PresetsFile(Presets::parse(&bytes)))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["balance.ron"]
+    }
+}
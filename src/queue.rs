@@ -1,32 +1,22 @@
-use std::collections::VecDeque;
-
-use bevy::{
-    ecs::system::{Command, EntityCommand},
-    prelude::*,
-    sprite::Anchor, audio::{Volume, VolumeLevel, PlaybackMode},
-};
+use bevy::{ecs::system::EntityCommand, prelude::*, sprite::Anchor};
 use bevy_mod_picking::prelude::*;
 
 use crate::{
-    consume_counter::ConsumeCount,
-    item::{ItemHandleIndex, ItemType},
+    item::ItemEntities,
     layers,
-    stress::{EmitStress, StressPopupText},
-    Sfx,
+    locale::{Locale, Localized},
+    sim::{ItemId, Sim, SimInput, SimInputs},
+    touch::is_touch,
 };
 
-#[derive(Component)]
-pub struct InQueue;
-
 #[derive(Component, Default)]
-pub struct Queue {
-    items: VecDeque<Entity>,
-}
+pub struct Queue;
 
 impl Queue {
-    const MAX_ITEMS: usize = 2;
+    /// Size of the queue art.
+    pub const SIZE: Vec2 = Vec2::new(267., 81.);
 
-    pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>, locale: Res<Locale>) {
         commands
             .spawn((
                 SpriteBundle {
@@ -39,8 +29,12 @@ impl Queue {
                     ..default()
                 },
                 PickableBundle::default(),
-                Queue::default(),
+                Queue,
                 On::<Pointer<Drop>>::commands_mut(move |event, commands| {
+                    // touch drops snap to the closest target instead
+                    if is_touch(event.pointer_id) {
+                        return;
+                    }
                     if let Some(ref mut e) = commands.get_entity(event.dropped) {
                         e.add(AddToQueue);
                     }
@@ -54,56 +48,56 @@ impl Queue {
                 },));
             });
 
-        ConsumeActive::spawn(&mut commands, &asset_server);
+        ConsumeActive::spawn(&mut commands, &asset_server, &locale);
     }
 }
 
-struct AddToQueue;
+pub struct AddToQueue;
 impl EntityCommand for AddToQueue {
     fn apply(self, id: Entity, world: &mut World) {
-        let e = world.entity(id);
-        if !e.contains::<ItemType>() || e.contains::<InQueue>() || e.contains::<ActiveItem>() {
-            return;
-        }
-
-        let mut queue = world.query::<&mut Queue>();
-        let mut queue = queue.single_mut(world);
-        if queue.items.len() >= Queue::MAX_ITEMS {
+        let Some(item) = world.get::<ItemId>(id).copied() else {
             return;
-        }
-        queue.items.push_back(id);
-        let mut e = world.entity_mut(id);
-        e.insert((InQueue, Pickable::IGNORE));
-        e.get_mut::<Transform>().unwrap().translation.z = layers::ITEMS;
-
-        let source = world.resource::<Sfx>().queue.clone();
-        world.spawn(AudioBundle {
-            source,
-            settings: PlaybackSettings {
-                mode: PlaybackMode::Despawn,
-                volume: Volume::Relative(VolumeLevel::new(0.9)),
-                ..default()
-            },
-        });
+        };
+        world
+            .resource_mut::<SimInputs>()
+            .push(SimInput::AddToQueue(item));
     }
 }
 
 pub fn in_queue_transforms(
-    mut items: Query<&mut Transform, With<InQueue>>,
-    queue: Query<(&Queue, &GlobalTransform)>,
+    sim: Res<Sim>,
+    entities: Res<ItemEntities>,
+    mut items: Query<&mut Transform, With<ItemId>>,
+    queue: Query<&GlobalTransform, With<Queue>>,
+    active_slot: Query<&GlobalTransform, With<ConsumeActive>>,
 ) {
     const FIRST_ITEM_OFFSET: Vec3 = Vec3::new(20.0, 0.0, 10.0);
-    let Ok((queue, queue_transform)) = queue.get_single() else {
+    let Ok(queue_transform) = queue.get_single() else {
         return;
     };
 
-    for (index, entity) in queue.items.iter().enumerate() {
-        let Ok(mut transform) = items.get_mut(*entity) else {
+    for (index, item) in sim.queue.iter().enumerate() {
+        let Some(mut transform) = entities
+            .0
+            .get(&item.id)
+            .and_then(|e| items.get_mut(*e).ok())
+        else {
             continue;
         };
         transform.translation =
             queue_transform.translation() + FIRST_ITEM_OFFSET - Vec3::X * (index * 75) as f32;
     }
+
+    let (Some(active), Ok(active_slot)) = (&sim.active, active_slot.get_single()) else {
+        return;
+    };
+    if let Some(mut transform) = entities
+        .0
+        .get(&active.item.id)
+        .and_then(|e| items.get_mut(*e).ok())
+    {
+        transform.translation = active_slot.translation() + Vec3::Z;
+    }
 }
 
 #[derive(Component)]
@@ -114,7 +108,7 @@ pub struct ConsumeMeter;
 pub struct ConsumeActive;
 
 impl ConsumeActive {
-    pub fn spawn(commands: &mut Commands, asset_server: &AssetServer) {
+    pub fn spawn(commands: &mut Commands, asset_server: &AssetServer, locale: &Locale) {
         commands
             .spawn((
                 ConsumeActive,
@@ -151,109 +145,31 @@ impl ConsumeActive {
                         ));
                     });
 
-                children.spawn(Text2dBundle {
-                    text: Text::from_section(
-                        "DRAG\nHERE",
-                        TextStyle {
-                            font: asset_server.load("chevyray_bird_seed.ttf"),
-                            font_size: 12.,
-                            color: Color::WHITE,
-                        },
-                    ),
-                    transform: Transform::from_xyz(0., 0., 0.5),
-                    ..default()
-                });
+                children.spawn((
+                    Localized("queue-drag-here"),
+                    Text2dBundle {
+                        text: Text::from_section(
+                            locale.get("queue-drag-here"),
+                            TextStyle {
+                                font: locale.font.clone(),
+                                font_size: 12.,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        transform: Transform::from_xyz(0., 0., 0.5),
+                        ..default()
+                    },
+                ));
             });
     }
 }
 
-/// Marker component for the active item.
-#[derive(Component)]
-pub struct ActiveItem(pub Timer);
-
-struct PopQueue;
-impl Command for PopQueue {
-    fn apply(self, world: &mut World) {
-        let mut queues = world.query::<&mut Queue>();
-        let Ok(mut queue) = queues.get_single_mut(world) else {
-            return;
-        };
-        let Some(active_item) = queue.items.pop_front() else {
-            return;
-        };
-
-        let mut active_slot = world.query_filtered::<&GlobalTransform, With<ConsumeActive>>();
-        let active_slot_translation = active_slot.single(world).translation();
-
-        let mut e = world.entity_mut(active_item);
-        let item_type = *e.get::<ItemType>().unwrap();
-        let mut transform = e.get_mut::<Transform>().unwrap();
-        transform.translation = active_slot_translation + Vec3::Z;
-        e.remove::<InQueue>().insert(ActiveItem(Timer::new(
-            item_type.consume_time(),
-            TimerMode::Once,
-        )));
-    }
-}
-
-pub fn check_active(mut commands: Commands, active_query: Query<(), With<ActiveItem>>) {
-    if active_query.is_empty() {
-        commands.add(PopQueue);
-    }
-}
-
-pub fn consume_active(
-    mut commands: Commands,
-    mut active_query: Query<(
-        Entity,
-        &ItemType,
-        &ItemHandleIndex,
-        &mut ActiveItem,
-        &GlobalTransform,
-    )>,
-    time: Res<Time>,
-    mut consumed: ResMut<ConsumeCount>,
-    sfx: Res<Sfx>,
-) {
-    let Ok((e, item_type, item_handle, mut timer, t)) = active_query.get_single_mut() else {
-        return;
-    };
-    if timer.0.tick(time.delta()).just_finished() {
-        commands.add(EmitStress(-1.));
-        commands.add(StressPopupText {
-            spawn_origin: t.translation() + 33. * Vec3::Y + 100. * Vec3::Z,
-            stress_value: -1.,
-        });
-        commands.entity(e).despawn();
-        commands.spawn(AudioBundle {
-            source: sfx.consume.clone(),
-            settings: PlaybackSettings::DESPAWN,
-        });
-        consumed.total += 1;
-        let item_totals = match item_type {
-            ItemType::Book => &mut consumed.books,
-            ItemType::Movie => &mut consumed.movies,
-            ItemType::Game => &mut consumed.games,
-            ItemType::Comic => &mut consumed.comics,
-        };
-        item_totals.total += 1;
-        item_totals
-            .items
-            .entry(item_handle.0)
-            .and_modify(|c| *c += 1)
-            .or_insert(1);
-    }
-}
-
-pub fn draw_timer(
-    active_query: Query<&mut ActiveItem>,
-    mut consume_meter: Query<&mut Sprite, With<ConsumeMeter>>,
-) {
-    let Ok(timer) = active_query.get_single() else {
+pub fn draw_timer(sim: Res<Sim>, mut consume_meter: Query<&mut Sprite, With<ConsumeMeter>>) {
+    let Some(ref active) = sim.active else {
         return;
     };
 
-    let fraction_left = timer.0.elapsed_secs() / timer.0.duration().as_secs_f32();
+    let fraction_left = active.fraction();
 
     let mut sprite = consume_meter.single_mut();
     let Some(ref mut size) = sprite.custom_size else {
@@ -0,0 +1,308 @@
+use std::{path::PathBuf, time::Duration};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::{Campaign, CurrentLevel},
+    consume_counter::ConsumeCount,
+    dialog::DialogDismissed,
+    difficulty::Difficulty,
+    game_state::GameState,
+    launch,
+    presets::Presets,
+    seed::RunSeed,
+    sim::{Balance, Sim, SimInput, SimInputs},
+};
+
+/// Records runs to a file with `--record <path>` and plays them back with
+/// `--replay <path>`.
+///
+/// A recording holds the seed, the difficulty, the length of every frame and
+/// every input, so feeding it back through [`SimInputs`] reproduces the run exactly.
+/// It's written when the run ends, or when the game is closed during it.
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StepDelta>()
+            .add_systems(
+                OnEnter(GameState::StartScreen),
+                Replay::start
+                    .after(CurrentLevel::clear)
+                    .run_if(resource_exists::<Replay>()),
+            )
+            .add_systems(
+                OnExit(GameState::Playing),
+                (
+                    Recorder::finish.run_if(resource_exists::<Recorder>()),
+                    Replay::check.run_if(resource_exists::<Replay>()),
+                ),
+            )
+            .add_systems(
+                Last,
+                Recorder::finish_on_exit
+                    .run_if(resource_exists::<Recorder>().and_then(in_state(GameState::Playing))),
+            );
+
+        if let Some(path) = launch::option("record") {
+            app.insert_resource(Recorder::new(path.into()));
+        }
+        if let Some(path) = launch::option("replay") {
+            match Recording::read(&path) {
+                Ok(recording) => {
+                    app.insert_resource(Replay::new(recording));
+                }
+                Err(e) => error!("could not read replay {path}: {e}"),
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Recording {
+    pub seed: u64,
+    #[serde(default)]
+    pub balance: Balance,
+    /// Id of the campaign level played, `None` for endless runs.
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Length of every frame of the run in nanoseconds, with how many frames
+    /// in a row took that long.
+    pub frames: Vec<(u64, u32)>,
+    /// Every input with the frame it happened on.
+    pub inputs: Vec<(u32, RecordedInput)>,
+    /// How the run ended, to check replays against.
+    pub result: Option<RunResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum RecordedInput {
+    Sim(SimInput),
+    DismissDialog,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RunResult {
+    pub consumed: ConsumeCount,
+    /// How long the run lasted.
+    pub elapsed: Duration,
+}
+
+impl RunResult {
+    fn of(sim: &Sim) -> Self {
+        Self {
+            consumed: sim.consumed.clone(),
+            elapsed: sim.elapsed,
+        }
+    }
+}
+
+impl Recording {
+    fn push_frame(&mut self, delta: Duration) {
+        let nanos = delta.as_nanos() as u64;
+        match self.frames.last_mut() {
+            Some((last, count)) if *last == nanos => *count += 1,
+            _ => self.frames.push((nanos, 1)),
+        }
+    }
+
+    pub fn read(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let recording: Recording = ron::from_str(&text).map_err(|e| e.to_string())?;
+        // a replay has to play by its own numbers, there's no falling back
+        recording.balance.validate().map_err(|e| e.to_string())?;
+        Ok(recording)
+    }
+}
+
+/// Time covered by the next sim step. The frame time, or the recorded one during a replay.
+#[derive(Resource, Default)]
+pub struct StepDelta(pub Duration);
+
+impl StepDelta {
+    pub fn from_time(time: Res<Time>, mut delta: ResMut<StepDelta>) {
+        delta.0 = time.delta();
+    }
+}
+
+/// Collects the current run while `--record` is given.
+#[derive(Resource)]
+pub struct Recorder {
+    path: PathBuf,
+    recording: Recording,
+    /// Frames recorded so far.
+    frames: u32,
+}
+
+impl Recorder {
+    /// Records runs to `path`, each run overwriting the last.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            recording: Recording::default(),
+            frames: 0,
+        }
+    }
+
+    pub fn start(
+        mut recorder: ResMut<Recorder>,
+        seed: Res<RunSeed>,
+        difficulty: Res<Difficulty>,
+        presets: Res<Presets>,
+        campaign: Res<Campaign>,
+        current_level: Res<CurrentLevel>,
+    ) {
+        recorder.recording = Recording {
+            seed: seed.current,
+            balance: current_level.balance(&campaign, &difficulty, &presets),
+            level: current_level.get(&campaign).map(|level| level.id.clone()),
+            ..default()
+        };
+        recorder.frames = 0;
+    }
+
+    /// Stores what is about to go into this frame's sim step.
+    pub fn record(
+        mut recorder: ResMut<Recorder>,
+        delta: Res<StepDelta>,
+        inputs: Res<SimInputs>,
+        mut dismissed: EventReader<DialogDismissed>,
+    ) {
+        let frame = recorder.frames;
+        recorder.frames += 1;
+        let recording = &mut recorder.recording;
+        recording.push_frame(delta.0);
+        let inputs = inputs
+            .0
+            .iter()
+            .map(|input| RecordedInput::Sim(*input))
+            .chain(dismissed.read().map(|_| RecordedInput::DismissDialog));
+        recording.inputs.extend(inputs.map(|input| (frame, input)));
+    }
+
+    fn finish(mut recorder: ResMut<Recorder>, sim: Res<Sim>) {
+        recorder.write(&sim);
+    }
+
+    /// Keeps the run when the game is closed in the middle of it.
+    fn finish_on_exit(
+        mut recorder: ResMut<Recorder>,
+        sim: Res<Sim>,
+        mut exit: EventReader<AppExit>,
+    ) {
+        if exit.read().next().is_some() {
+            recorder.write(&sim);
+        }
+    }
+
+    fn write(&mut self, sim: &Sim) {
+        self.recording.result = Some(RunResult::of(sim));
+        let written = ron::to_string(&self.recording)
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(&self.path, text).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => info!("recorded run to {}", self.path.display()),
+            Err(e) => error!("could not write {}: {e}", self.path.display()),
+        }
+    }
+}
+
+/// Plays a recording back in place of the player.
+#[derive(Resource)]
+pub struct Replay {
+    recording: Recording,
+    frame: usize,
+    input: usize,
+    /// Index into the recorded frame lengths, and how many frames of it were played.
+    run: usize,
+    repeat: u32,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+            input: 0,
+            run: 0,
+            repeat: 0,
+        }
+    }
+
+    /// Replays play by the recorded numbers whatever difficulty is picked.
+    pub fn balance(&self) -> Balance {
+        self.recording.balance
+    }
+
+    /// Skips the start screen with the recording's seed and level.
+    fn start(
+        replay: Res<Replay>,
+        mut seed: ResMut<RunSeed>,
+        campaign: Res<Campaign>,
+        mut current_level: ResMut<CurrentLevel>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        seed.chosen = Some(replay.recording.seed);
+        if let Some(id) = &replay.recording.level {
+            current_level.0 = campaign.levels.iter().position(|level| level.id == *id);
+            if current_level.0.is_none() {
+                warn!("replay level {id} is not in the campaign, playing an endless run");
+            }
+        }
+        state.set(GameState::Playing);
+    }
+
+    /// Swaps the player's inputs for the recorded ones and ends the run when
+    /// the recording does.
+    pub fn feed(
+        mut replay: ResMut<Replay>,
+        mut delta: ResMut<StepDelta>,
+        mut inputs: ResMut<SimInputs>,
+        mut dismissed: ResMut<Events<DialogDismissed>>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        inputs.0.clear();
+        dismissed.clear();
+
+        let Some(&(nanos, count)) = replay.recording.frames.get(replay.run) else {
+            delta.0 = Duration::ZERO;
+            state.set(GameState::Failed);
+            return;
+        };
+        delta.0 = Duration::from_nanos(nanos);
+
+        let frame = replay.frame as u32;
+        while let Some((at, input)) = replay.recording.inputs.get(replay.input) {
+            if *at != frame {
+                break;
+            }
+            match input {
+                RecordedInput::Sim(input) => inputs.push(*input),
+                RecordedInput::DismissDialog => dismissed.send(DialogDismissed),
+            }
+            replay.input += 1;
+        }
+        replay.frame += 1;
+        replay.repeat += 1;
+        if replay.repeat == count {
+            replay.run += 1;
+            replay.repeat = 0;
+        }
+    }
+
+    /// Reports whether the replay ended the same way as the recorded run.
+    fn check(mut commands: Commands, replay: Res<Replay>, sim: Res<Sim>) {
+        let result = RunResult::of(&sim);
+        match &replay.recording.result {
+            Some(expected) if *expected == result => {
+                info!("replay matched the recording: {result:?}");
+            }
+            Some(expected) => {
+                error!("replay diverged, expected {expected:?} but got {result:?}");
+            }
+            None => info!("replay finished: {result:?}"),
+        }
+        // later runs are played normally
+        commands.remove_resource::<Replay>();
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::{app::AppExit, ecs::system::Command, prelude::*};
+use bevy_rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::{Campaign, CurrentLevel},
+    game_state::GameState,
+    seed::RunSeed,
+    sim::{Sim, SimState},
+    storage,
+};
+
+/// Keeps a run in progress between launches. Quitting the game mid-run or
+/// picking "Save and quit" in the pause menu saves it, and the start screen
+/// offers to continue it. Continuing a run uses the save up, starting any
+/// other run throws it away.
+pub struct SavedRunPlugin;
+impl Plugin for SavedRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), SavedRun::clear)
+            .add_systems(Last, save_on_exit.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// A run to pick up later. Inserted as a resource, it's the run the next
+/// [`GameState::Playing`] continues.
+#[derive(Resource, Serialize, Deserialize)]
+pub struct SavedRun {
+    /// Id of the campaign level played, `None` for endless runs.
+    level: Option<String>,
+    seed: u64,
+    /// The rng as it was, so the run carries on with the same luck.
+    rng: GlobalEntropy<ChaCha8Rng>,
+    sim: SimState,
+}
+
+impl SavedRun {
+    const KEY: &'static str = "run";
+
+    pub fn load() -> Option<SavedRun> {
+        storage::load(Self::KEY)
+    }
+
+    /// Index of the level the run was playing, `None` for endless runs.
+    pub fn level(&self, campaign: &Campaign) -> Result<Option<usize>, String> {
+        let Some(id) = &self.level else {
+            return Ok(None);
+        };
+        campaign
+            .levels
+            .iter()
+            .position(|level| &level.id == id)
+            .map(Some)
+            .ok_or_else(|| format!("there is no level {id}"))
+    }
+
+    /// Swaps the run that just started for the saved one, once its [`Sim`] is in.
+    pub fn resume(world: &mut World) {
+        let Some(saved) = world.remove_resource::<SavedRun>() else {
+            return;
+        };
+        if let Err(e) = world.resource_mut::<Sim>().resume(saved.sim) {
+            warn!("could not continue the saved run: {e}");
+            return;
+        }
+        world.resource_mut::<RunSeed>().current = saved.seed;
+        world.insert_resource(saved.rng);
+    }
+
+    fn clear() {
+        storage::remove(Self::KEY);
+    }
+}
+
+/// Saves the run in progress, unless it's already over.
+pub struct SaveRun;
+
+impl Command for SaveRun {
+    fn apply(self, world: &mut World) {
+        let Some(sim) = world.get_resource::<Sim>() else {
+            return;
+        };
+        if sim.failed() || sim.won() {
+            return;
+        }
+        let sim = sim.state();
+        let level = world
+            .resource::<CurrentLevel>()
+            .get(world.resource::<Campaign>())
+            .map(|level| level.id.clone());
+        let seed = world.resource::<RunSeed>().current;
+        let rng = world.resource::<GlobalEntropy<ChaCha8Rng>>().clone();
+        storage::save(
+            SavedRun::KEY,
+            &SavedRun {
+                level,
+                seed,
+                rng,
+                sim,
+            },
+        );
+    }
+}
+
+fn save_on_exit(mut commands: Commands, mut exit: EventReader<AppExit>) {
+    if exit.read().next().is_some() {
+        commands.add(SaveRun);
+    }
+}
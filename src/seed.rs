@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_rand::prelude::*;
+use rand_core::RngCore;
+
+use crate::{launch, locale::Locale};
+
+/// Makes every run reproducible from a single number.
+///
+/// The seed can be given with `--seed <n>` on the command line, `?seed=<n>`
+/// in the page URL on the web, or typed on the start screen. Without one each
+/// run gets a fresh random seed.
+pub struct SeedPlugin;
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunSeed {
+            chosen: launch_seed(),
+            current: 0,
+        });
+    }
+}
+
+#[derive(Resource)]
+pub struct RunSeed {
+    /// Seed picked by the player. Empty means a random seed per run.
+    pub chosen: Option<u64>,
+    /// Seed of the current or last run.
+    pub current: u64,
+}
+
+impl RunSeed {
+    /// Reseeds the global rng at the start of a run, before anything random happens.
+    pub fn reseed(mut seed: ResMut<RunSeed>, mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>) {
+        seed.current = seed.chosen.unwrap_or_else(|| rng.next_u64());
+        rng.reseed(seed_bytes(seed.current));
+    }
+
+    pub fn label(&self, locale: &Locale) -> String {
+        match self.chosen {
+            Some(seed) => seed.to_string(),
+            None => locale.get("seed-random"),
+        }
+    }
+}
+
+/// The rng seed a run seed stands for.
+pub fn seed_bytes(seed: u64) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    bytes
+}
+
+fn launch_seed() -> Option<u64> {
+    parse_seed(&launch::option("seed")?)
+}
+
+fn parse_seed(value: &str) -> Option<u64> {
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            warn!("ignoring seed {value:?}, it should be a whole number");
+            None
+        }
+    }
+}
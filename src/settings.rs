@@ -0,0 +1,585 @@
+use bevy::{
+    audio::{PlaybackMode, Volume, VolumeLevel},
+    prelude::*,
+    ui::{FocusPolicy, RelativeCursorPosition},
+    window::{PrimaryWindow, WindowMode, WindowResized, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::Palette,
+    layers,
+    locale::{Locale, Locales, Localized},
+    storage,
+};
+
+/// Size of the game in logical pixels. Window scale multiplies this.
+pub const VIEW_SIZE: Vec2 = Vec2::new(640., 360.);
+
+/// Audio, display and accessibility options, saved between sessions.
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<SettingsState>()
+            .add_systems(OnEnter(SettingsState::Open), spawn_menu)
+            .add_systems(OnExit(SettingsState::Open), (despawn_menu, Settings::save))
+            .add_systems(
+                Update,
+                (
+                    SettingsButton::handle_clicks,
+                    SliderBar::drag,
+                    close_on_escape,
+                    SettingValue::update,
+                )
+                    .run_if(in_state(SettingsState::Open)),
+            )
+            .add_systems(
+                Update,
+                OpenSettingsButton::handle_clicks.run_if(in_state(SettingsState::Closed)),
+            )
+            .add_systems(
+                Update,
+                (
+                    Settings::apply_window.run_if(resource_changed::<Settings>()),
+                    Settings::apply_volume.run_if(resource_changed::<Settings>()),
+                    fit_ui_scale,
+                ),
+            );
+    }
+}
+
+/// The settings screen is drawn over whatever screen opened it.
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// 0-1
+    pub master_volume: f32,
+    /// 0-1
+    pub music_volume: f32,
+    /// 0-1
+    pub sfx_volume: f32,
+    /// Whole multiples of [`VIEW_SIZE`] keep the pixel art crisp.
+    pub window_scale: u32,
+    pub fullscreen: bool,
+    /// Keeps the stress popups from drifting up the screen.
+    pub reduced_motion: bool,
+    /// Colors for warnings and stress popups.
+    pub palette: Palette,
+    /// Adds signs to state that is otherwise only shown by color.
+    pub shape_cues: bool,
+    /// Code of the language the game is shown in.
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            music_volume: 1.,
+            sfx_volume: 1.,
+            window_scale: 1,
+            fullscreen: false,
+            reduced_motion: false,
+            palette: Palette::Standard,
+            shape_cues: false,
+            language: "en".to_string(),
+        }
+    }
+}
+
+/// Opens the settings screen when clicked.
+#[derive(Component)]
+pub struct OpenSettingsButton;
+
+impl OpenSettingsButton {
+    fn handle_clicks(
+        q: Query<&Interaction, (Changed<Interaction>, With<OpenSettingsButton>)>,
+        mut state: ResMut<NextState<SettingsState>>,
+    ) {
+        if q.iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            state.set(SettingsState::Open);
+        }
+    }
+}
+
+/// Which volume slider a sound follows.
+#[derive(Clone, Copy)]
+pub enum AudioChannel {
+    Music,
+    Sfx,
+}
+
+/// Keeps a playing sound in line with the volume settings.
+#[derive(Component)]
+pub struct SoundVolume {
+    channel: AudioChannel,
+    /// Volume of this sound before the settings are applied.
+    base: f32,
+}
+
+impl Settings {
+    const KEY: &'static str = "settings";
+    const MAX_WINDOW_SCALE: u32 = 3;
+
+    /// The saved settings, or the defaults. Values a hand edit put out of
+    /// range are pulled back in.
+    pub fn load() -> Self {
+        let mut settings: Settings = storage::load(Self::KEY).unwrap_or_default();
+        settings.window_scale = settings.window_scale.clamp(1, Self::MAX_WINDOW_SCALE);
+        for volume in [
+            &mut settings.master_volume,
+            &mut settings.music_volume,
+            &mut settings.sfx_volume,
+        ] {
+            *volume = if volume.is_nan() {
+                1.
+            } else {
+                volume.clamp(0., 1.)
+            };
+        }
+        settings
+    }
+
+    fn save(settings: Res<Settings>) {
+        storage::save(Self::KEY, &*settings);
+    }
+
+    pub fn volume(&self, channel: AudioChannel) -> f32 {
+        self.master_volume
+            * match channel {
+                AudioChannel::Music => self.music_volume,
+                AudioChannel::Sfx => self.sfx_volume,
+            }
+    }
+
+    /// Every sound in the game is spawned through this so it respects the volume settings.
+    pub fn sound(
+        &self,
+        source: Handle<AudioSource>,
+        mode: PlaybackMode,
+        channel: AudioChannel,
+        base: f32,
+    ) -> (AudioBundle, SoundVolume) {
+        (
+            AudioBundle {
+                source,
+                settings: PlaybackSettings {
+                    mode,
+                    volume: Volume::Relative(VolumeLevel::new(base * self.volume(channel))),
+                    ..default()
+                },
+            },
+            SoundVolume { channel, base },
+        )
+    }
+
+    pub fn window_resolution(&self) -> WindowResolution {
+        let scale = self.window_scale as f32;
+        WindowResolution::new(VIEW_SIZE.x * scale, VIEW_SIZE.y * scale)
+            .with_scale_factor_override(scale as f64)
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    fn apply_window(settings: Res<Settings>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+        let Ok(mut window) = windows.get_single_mut() else {
+            return;
+        };
+        let mode = settings.window_mode();
+        if window.mode != mode {
+            window.mode = mode;
+        }
+        let resolution = settings.window_resolution();
+        if window.resolution.scale_factor_override() != resolution.scale_factor_override() {
+            window.resolution = resolution;
+        }
+    }
+
+    fn apply_volume(settings: Res<Settings>, sounds: Query<(&AudioSink, &SoundVolume)>) {
+        for (sink, sound) in &sounds {
+            sink.set_volume(sound.base * settings.volume(sound.channel));
+        }
+    }
+}
+
+/// Scales the UI with the window so menus line up with the art in fullscreen.
+fn fit_ui_scale(
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if resized.read().count() == 0 {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    ui_scale.0 = (window.width() / VIEW_SIZE.x).min(window.height() / VIEW_SIZE.y) as f64;
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum Setting {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    WindowScale,
+    Fullscreen,
+    ReducedMotion,
+    Palette,
+    ShapeCues,
+    Language,
+}
+
+impl Setting {
+    const ALL: [Setting; 9] = [
+        Setting::MasterVolume,
+        Setting::MusicVolume,
+        Setting::SfxVolume,
+        Setting::WindowScale,
+        Setting::Fullscreen,
+        Setting::ReducedMotion,
+        Setting::Palette,
+        Setting::ShapeCues,
+        Setting::Language,
+    ];
+
+    /// Key of the setting's name.
+    fn label(self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "setting-master-volume",
+            Setting::MusicVolume => "setting-music-volume",
+            Setting::SfxVolume => "setting-sfx-volume",
+            Setting::WindowScale => "setting-window-scale",
+            Setting::Fullscreen => "setting-fullscreen",
+            Setting::ReducedMotion => "setting-reduced-motion",
+            Setting::Palette => "setting-colors",
+            Setting::ShapeCues => "setting-shape-cues",
+            Setting::Language => "setting-language",
+        }
+    }
+
+    /// The volume a slider setting controls.
+    fn volume(self, settings: &Settings) -> Option<f32> {
+        match self {
+            Setting::MasterVolume => Some(settings.master_volume),
+            Setting::MusicVolume => Some(settings.music_volume),
+            Setting::SfxVolume => Some(settings.sfx_volume),
+            _ => None,
+        }
+    }
+
+    fn set_volume(self, settings: &mut Settings, volume: f32) {
+        let volume = volume.clamp(0., 1.);
+        match self {
+            Setting::MasterVolume => settings.master_volume = volume,
+            Setting::MusicVolume => settings.music_volume = volume,
+            Setting::SfxVolume => settings.sfx_volume = volume,
+            _ => {}
+        }
+    }
+
+    fn value(self, settings: &Settings, locale: &Locale, locales: &Locales) -> String {
+        let on_off = |on| locale.get(if on { "setting-on" } else { "setting-off" });
+        match self {
+            Setting::MasterVolume => format!("{:.0}%", settings.master_volume * 100.),
+            Setting::MusicVolume => format!("{:.0}%", settings.music_volume * 100.),
+            Setting::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.),
+            Setting::WindowScale => {
+                locale.format("setting-scale-value", &[("scale", &settings.window_scale)])
+            }
+            Setting::Fullscreen => on_off(settings.fullscreen),
+            Setting::ReducedMotion => on_off(settings.reduced_motion),
+            Setting::Palette => locale.get(settings.palette.label()),
+            Setting::ShapeCues => on_off(settings.shape_cues),
+            Setting::Language => locales.get(&settings.language).name.clone(),
+        }
+    }
+
+    /// Moves the setting a step up or down. On/off settings flip either way.
+    fn step(self, settings: &mut Settings, locales: &Locales, up: bool) {
+        if let Some(volume) = self.volume(settings) {
+            let step = if up { 0.1 } else { -0.1 };
+            self.set_volume(settings, ((volume + step) * 10.).round() / 10.);
+            return;
+        }
+        match self {
+            Setting::WindowScale => {
+                let max = Settings::MAX_WINDOW_SCALE;
+                settings.window_scale = if up {
+                    settings.window_scale % max + 1
+                } else {
+                    (settings.window_scale + max - 2) % max + 1
+                };
+            }
+            Setting::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Setting::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            Setting::Palette => {
+                settings.palette = if up {
+                    settings.palette.next()
+                } else {
+                    settings.palette.previous()
+                };
+            }
+            Setting::ShapeCues => settings.shape_cues = !settings.shape_cues,
+            Setting::Language => {
+                settings.language = locales.step(&settings.language, up).code.clone();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Text showing the current value of a setting.
+#[derive(Component)]
+struct SettingValue(Setting);
+
+impl SettingValue {
+    fn update(
+        settings: Res<Settings>,
+        locale: Res<Locale>,
+        locales: Res<Locales>,
+        mut texts: Query<(&mut Text, &SettingValue)>,
+        mut fills: Query<(&mut Style, &SliderFill)>,
+    ) {
+        if !settings.is_changed() && !locale.is_changed() {
+            return;
+        }
+        for (mut text, value) in &mut texts {
+            text.sections[0].value = value.0.value(&settings, &locale, &locales);
+        }
+        for (mut style, fill) in &mut fills {
+            if let Some(volume) = fill.0.volume(&settings) {
+                style.width = Val::Percent(volume * 100.);
+            }
+        }
+    }
+}
+
+/// Volume slider. Click or drag along it to set the volume.
+#[derive(Component)]
+struct SliderBar(Setting);
+
+/// Filled part of a [`SliderBar`].
+#[derive(Component)]
+struct SliderFill(Setting);
+
+impl SliderBar {
+    const WIDTH: f32 = 80.;
+
+    fn drag(
+        bars: Query<(&Interaction, &RelativeCursorPosition, &SliderBar)>,
+        mut settings: ResMut<Settings>,
+    ) {
+        for (interaction, cursor, bar) in &bars {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let Some(position) = cursor.normalized else {
+                continue;
+            };
+            let volume = (position.x.clamp(0., 1.) * 20.).round() / 20.;
+            if bar.0.volume(&settings) != Some(volume) {
+                bar.0.set_volume(&mut settings, volume);
+            }
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+    Down(Setting),
+    Up(Setting),
+    Done,
+}
+
+impl SettingsButton {
+    fn handle_clicks(
+        q: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+        mut settings: ResMut<Settings>,
+        locales: Res<Locales>,
+        mut state: ResMut<NextState<SettingsState>>,
+    ) {
+        for (interaction, button) in &q {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            match button {
+                SettingsButton::Down(setting) => setting.step(&mut settings, &locales, false),
+                SettingsButton::Up(setting) => setting.step(&mut settings, &locales, true),
+                SettingsButton::Done => state.set(SettingsState::Closed),
+            }
+        }
+    }
+}
+
+fn close_on_escape(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<NextState<SettingsState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(SettingsState::Closed);
+    }
+}
+
+#[derive(Component)]
+struct SettingsMenuMarker;
+
+fn spawn_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    locales: Res<Locales>,
+) {
+    let style = TextStyle {
+        font: locale.font.clone(),
+        font_size: 16.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let button = |width: f32| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(22.),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::DARK_GRAY.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            SettingsMenuMarker,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.),
+                    ..default()
+                },
+                background_color: Color::BLACK.with_a(0.8).into(),
+                // keeps clicks off whatever screen is underneath
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(layers::UI as i32 + 10),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Localized("settings-title"),
+                TextBundle::from_section(
+                    locale.get("settings-title"),
+                    TextStyle {
+                        font_size: 24.,
+                        ..style.clone()
+                    },
+                ),
+            ));
+
+            for setting in Setting::ALL {
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(6.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Localized(setting.label()),
+                            TextBundle::from_section(locale.get(setting.label()), style.clone())
+                                .with_style(Style {
+                                    width: Val::Px(140.),
+                                    ..default()
+                                }),
+                        ));
+                        row.spawn((SettingsButton::Down(setting), button(22.)))
+                            .with_children(|b| {
+                                b.spawn(TextBundle::from_section("<", style.clone()));
+                            });
+                        if let Some(volume) = setting.volume(&settings) {
+                            row.spawn((
+                                SliderBar(setting),
+                                RelativeCursorPosition::default(),
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(SliderBar::WIDTH),
+                                        height: Val::Px(10.),
+                                        ..default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..default()
+                                },
+                            ))
+                            .with_children(|bar| {
+                                bar.spawn((
+                                    SliderFill(setting),
+                                    NodeBundle {
+                                        style: Style {
+                                            width: Val::Percent(volume * 100.),
+                                            height: Val::Percent(100.),
+                                            ..default()
+                                        },
+                                        background_color: Color::rgb_u8(217, 155, 150).into(),
+                                        ..default()
+                                    },
+                                ));
+                            });
+                        } else {
+                            row.spawn((
+                                SettingValue(setting),
+                                TextBundle::from_section(
+                                    setting.value(&settings, &locale, &locales),
+                                    style.clone(),
+                                )
+                                .with_text_alignment(TextAlignment::Center)
+                                .with_style(Style {
+                                    width: Val::Px(SliderBar::WIDTH),
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                }),
+                            ));
+                        }
+                        row.spawn((SettingsButton::Up(setting), button(22.)))
+                            .with_children(|b| {
+                                b.spawn(TextBundle::from_section(">", style.clone()));
+                            });
+                    });
+            }
+
+            children
+                .spawn((SettingsButton::Done, button(100.)))
+                .with_children(|b| {
+                    b.spawn((
+                        Localized("settings-done"),
+                        TextBundle::from_section(locale.get("settings-done"), style.clone()),
+                    ));
+                });
+        });
+}
+
+fn despawn_menu(mut commands: Commands, q: Query<Entity, With<SettingsMenuMarker>>) {
+    for e in &q {
+        commands.entity(e).despawn_recursive();
+    }
+}
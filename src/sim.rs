@@ -0,0 +1,1406 @@
+//! Headless model of the backlog rules.
+//!
+//! [`Sim`] owns the stacks, the queue, the item being consumed, stress and the
+//! day timer. It only moves forward through [`Sim::step`], so the rules can be
+//! run and balanced without a window. The ECS systems feed it [`SimInput`]s
+//! and mirror its state onto sprites.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::{Component, Event, Resource, SystemSet};
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    consume_counter::ConsumeCount,
+    curve::{CurveKey, DifficultyCurve, Keyframe},
+    item::ItemType,
+};
+
+/// Stable id of an item for the whole run.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct ItemId(pub u64);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub id: ItemId,
+    pub item_type: ItemType,
+    pub variant: Variant,
+    /// Horizontal jitter when the item sits on a stack, up to [`MAX_OFFSET`].
+    pub offset: f32,
+    /// Stack space the item takes.
+    pub height: f32,
+    pub consume_time: Duration,
+}
+
+/// Furthest an item sits to either side of its stack.
+pub const MAX_OFFSET: f32 = 3.5;
+
+/// Which item of its category something is.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Variant {
+    /// An item listed in the catalog.
+    Catalog(usize),
+    /// A blank from the catalog with a title generated from `title` printed on it.
+    Generated { blank: usize, title: u64 },
+}
+
+/// Rules for one item in the catalog.
+#[derive(Clone, Copy, Debug)]
+pub struct ItemRules {
+    pub height: f32,
+    pub consume_time: Duration,
+    /// How likely this item is to be bought compared to the rest of its category.
+    pub weight: f32,
+}
+
+/// A category and every item that can be bought in it.
+#[derive(Clone, Debug)]
+pub struct CategoryRules {
+    pub item_type: ItemType,
+    pub items: Vec<ItemRules>,
+    pub generated: Option<GeneratedRules>,
+}
+
+/// Rules for items with generated titles.
+#[derive(Clone, Copy, Debug)]
+pub struct GeneratedRules {
+    /// Number of blanks to pick from.
+    pub blanks: usize,
+    pub rules: ItemRules,
+}
+
+/// Numbers that decide how hard a run is.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Balance {
+    /// Stress a run starts with.
+    pub start_stress: f32,
+    /// The run is lost above this much stress.
+    pub fail_stress: f32,
+    /// Length of a day in seconds, before the [`DifficultyCurve`] shortens it.
+    pub day_length: f32,
+    pub max_stack_height: f32,
+    pub max_queue_items: usize,
+    /// End of day stress when something was bought that day.
+    pub bought_penalty: f32,
+    /// End of day stress when nothing was bought.
+    pub missed_buy_penalty: f32,
+    /// End of day stress for each item on the wrong stack.
+    pub mismatch_penalty: f32,
+    /// Stress a buy relieves.
+    pub buy_relief: f32,
+    /// Stress relieved when an item is finished.
+    pub consume_relief: f32,
+    /// Fewest items each stack of an endless run starts with.
+    pub min_start_items: usize,
+    /// Most items each stack of an endless run starts with.
+    pub max_start_items: usize,
+    /// One in this many days announces a sale.
+    pub sale_chance: usize,
+    /// Seconds from a sale's announcement to its start.
+    pub sale_lead_time: f32,
+    /// Seconds a sale is on for.
+    pub sale_length: f32,
+    /// Stress a buy relieves while the sale is on.
+    pub sale_relief: f32,
+    /// Fewest items a buy brings while the sale is on.
+    pub min_sale_items: usize,
+    /// Most items a buy brings while the sale is on.
+    pub max_sale_items: usize,
+    /// Items take this much of their usual time to consume on weekends.
+    pub weekend_consume_time: f32,
+}
+
+impl Balance {
+    pub const NORMAL: Balance = Balance {
+        start_stress: 10.,
+        fail_stress: 100.,
+        day_length: 10.,
+        max_stack_height: 240.,
+        max_queue_items: 2,
+        bought_penalty: 2.,
+        missed_buy_penalty: 5.,
+        mismatch_penalty: 0.5,
+        buy_relief: 1.,
+        consume_relief: 1.,
+        min_start_items: 3,
+        max_start_items: 8,
+        sale_chance: 4,
+        sale_lead_time: 5.,
+        sale_length: 12.,
+        sale_relief: 3.,
+        min_sale_items: 2,
+        max_sale_items: 4,
+        weekend_consume_time: 0.75,
+    };
+
+    /// Checks the numbers make sense, naming the first one that doesn't.
+    pub fn validate(&self) -> Result<(), InvalidBalance> {
+        let numbers = [
+            ("start_stress", self.start_stress),
+            ("fail_stress", self.fail_stress),
+            ("day_length", self.day_length),
+            ("max_stack_height", self.max_stack_height),
+            ("bought_penalty", self.bought_penalty),
+            ("missed_buy_penalty", self.missed_buy_penalty),
+            ("mismatch_penalty", self.mismatch_penalty),
+            ("buy_relief", self.buy_relief),
+            ("consume_relief", self.consume_relief),
+            ("sale_lead_time", self.sale_lead_time),
+            ("sale_length", self.sale_length),
+            ("sale_relief", self.sale_relief),
+            ("weekend_consume_time", self.weekend_consume_time),
+        ];
+        let checks = [
+            ("start_stress", self.start_stress >= 0.),
+            ("fail_stress", self.fail_stress > self.start_stress),
+            ("day_length", self.day_length > 0.),
+            ("max_stack_height", self.max_stack_height > 0.),
+            ("max_queue_items", self.max_queue_items > 0),
+            ("bought_penalty", self.bought_penalty >= 0.),
+            ("missed_buy_penalty", self.missed_buy_penalty >= 0.),
+            ("mismatch_penalty", self.mismatch_penalty >= 0.),
+            ("buy_relief", self.buy_relief >= 0.),
+            ("consume_relief", self.consume_relief >= 0.),
+            (
+                "min_start_items",
+                self.min_start_items <= self.max_start_items,
+            ),
+            ("sale_chance", self.sale_chance > 0),
+            ("sale_lead_time", self.sale_lead_time >= 0.),
+            ("sale_length", self.sale_length > 0.),
+            ("sale_relief", self.sale_relief >= 0.),
+            ("min_sale_items", self.min_sale_items > 0),
+            ("max_sale_items", self.max_sale_items >= self.min_sale_items),
+            ("weekend_consume_time", self.weekend_consume_time > 0.),
+        ];
+        let finite = numbers.map(|(name, value)| (name, value.is_finite()));
+        match finite.iter().chain(&checks).find(|(_, valid)| !valid) {
+            Some((name, _)) => Err(InvalidBalance(name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The [`Balance`] field that's out of range.
+#[derive(Debug, Error)]
+#[error("{0} is negative or impossible")]
+pub struct InvalidBalance(pub &'static str);
+
+impl Default for Balance {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// What a campaign level changes about a run.
+#[derive(Clone, Debug)]
+pub struct LevelRules {
+    /// Items each stack starts with, in stack order.
+    pub stacks: Vec<usize>,
+    /// Scripted happenings in order of time.
+    pub script: Vec<Cue>,
+    pub goal: Goal,
+}
+
+/// Something that happens at a set time of a level.
+#[derive(Clone, Copy, Debug)]
+pub struct Cue {
+    pub at: Duration,
+    pub action: CueAction,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum CueAction {
+    /// Buys `count` items of the category at this index onto random stacks.
+    Buy { category: usize, count: usize },
+    /// Only announced with [`SimEvent::Cue`], the view knows what to say.
+    Dialog,
+}
+
+/// What a level asks of the player.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum Goal {
+    /// Consume `items` items before `days` days are over.
+    Consume { items: u32, days: usize },
+    /// Keep stress below `stress` for `secs` seconds.
+    KeepStressUnder { stress: f32, secs: f32 },
+}
+
+pub struct SimStack {
+    pub item_type: ItemType,
+    pub items: Vec<Item>,
+}
+
+impl SimStack {
+    fn new(item_type: ItemType) -> Self {
+        Self {
+            item_type,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn current_height(&self) -> f32 {
+        self.items.iter().map(|item| item.height).sum()
+    }
+
+    /// Number of items that don't belong on this stack.
+    pub fn mismatched(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.item_type != self.item_type)
+            .count()
+    }
+}
+
+/// The item in the consume slot.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Consuming {
+    pub item: Item,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+impl Consuming {
+    pub fn fraction(&self) -> f32 {
+        self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
+    }
+}
+
+/// An item that was taken off a stack and hasn't landed anywhere yet.
+pub struct Held {
+    pub item: Item,
+    dragging: bool,
+}
+
+/// Length of a day. A buy is expected once per day.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct TodayTimer {
+    elapsed: Duration,
+    duration: Duration,
+    pub clicked_today: bool,
+}
+
+impl TodayTimer {
+    fn new(secs: f32) -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            duration: Duration::from_secs_f32(secs),
+            clicked_today: false,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
+    }
+
+    #[cfg(feature = "debug_console")]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    #[cfg(feature = "debug_console")]
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed)
+    }
+
+    /// Returns true when the day wrapped around.
+    fn tick(&mut self, dt: Duration) -> bool {
+        self.elapsed += dt;
+        if self.elapsed < self.duration {
+            return false;
+        }
+        self.elapsed -= self.duration;
+        if self.elapsed >= self.duration {
+            self.elapsed = Duration::ZERO;
+        }
+        true
+    }
+}
+
+/// What happened during one day.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct DayStats {
+    pub items_bought: u32,
+    pub items_consumed: u32,
+    /// Stress at the end of the day minus stress at the start.
+    pub stress_change: f32,
+    /// Whether the day's buy happened.
+    pub bought: bool,
+}
+
+impl DayStats {
+    fn add(mut self, other: DayStats) -> DayStats {
+        self.items_bought += other.items_bought;
+        self.items_consumed += other.items_consumed;
+        self.stress_change += other.stress_change;
+        self.bought |= other.bought;
+        self
+    }
+}
+
+/// Days and weeks of the run, with what happened on each day.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Calendar {
+    /// Every day that has ended, oldest first.
+    pub days: Vec<DayStats>,
+    /// The day in progress so far.
+    pub today: DayStats,
+    /// Stress when the current day started.
+    start_stress: f32,
+}
+
+impl Calendar {
+    pub const DAYS_PER_WEEK: usize = 7;
+    /// Days of the week from this one on are the weekend.
+    const WEEKEND: usize = 5;
+
+    fn new(stress: f32) -> Self {
+        Self {
+            days: Vec::new(),
+            today: DayStats::default(),
+            start_stress: stress,
+        }
+    }
+
+    /// The current day, counting from zero.
+    pub fn day(&self) -> usize {
+        self.days.len()
+    }
+
+    /// The current week, counting from zero.
+    pub fn week(&self) -> usize {
+        self.day() / Self::DAYS_PER_WEEK
+    }
+
+    /// The current day of the week, zero being the first.
+    pub fn weekday(&self) -> usize {
+        self.day() % Self::DAYS_PER_WEEK
+    }
+
+    pub fn is_weekend(&self) -> bool {
+        self.weekday() >= Self::WEEKEND
+    }
+
+    /// The days of a week that has ended, oldest first.
+    pub fn week_days(&self, week: usize) -> &[DayStats] {
+        let start = (week * Self::DAYS_PER_WEEK).min(self.days.len());
+        let end = (start + Self::DAYS_PER_WEEK).min(self.days.len());
+        &self.days[start..end]
+    }
+
+    /// Everything that happened in a week that has ended.
+    pub fn week_total(&self, week: usize) -> DayStats {
+        self.week_days(week)
+            .iter()
+            .fold(DayStats::default(), |total, day| total.add(*day))
+    }
+
+    /// Files today's stats and starts the next day. Returns true when that
+    /// finished a week.
+    fn end_day(&mut self, bought: bool, stress: f32) -> bool {
+        self.today.bought = bought;
+        self.today.stress_change = stress - self.start_stress;
+        self.days.push(std::mem::take(&mut self.today));
+        self.start_stress = stress;
+        self.weekday() == 0
+    }
+}
+
+/// A category on sale for a while. Sales are announced a little before they
+/// start so players can plan around them.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Sale {
+    pub item_type: ItemType,
+    /// Time until the sale starts. Zero once it's on.
+    pub starts_in: Duration,
+    /// Time left once it's on.
+    pub remaining: Duration,
+}
+
+impl Sale {
+    pub fn active(&self) -> bool {
+        self.starts_in.is_zero()
+    }
+}
+
+/// Everything the player can do, in the order it happened during a frame.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SimInput {
+    /// The buy button was clicked.
+    Buy,
+    /// An item was picked up off its stack.
+    RemoveFromStack(ItemId),
+    /// A held item was dropped on the stack with this index.
+    AddToStack { item: ItemId, stack: usize },
+    /// A held item was dropped on the queue.
+    AddToQueue(ItemId),
+    /// The player let go of an item. If nothing took it this step it goes
+    /// back on a random stack.
+    Release(ItemId),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum StressCause {
+    Buy,
+    Consume,
+    EndOfDay,
+}
+
+/// What happened during a [`Sim::step`], for sounds, popups and the event log.
+#[derive(Event, Clone, Copy, Debug, Serialize)]
+pub enum SimEvent {
+    /// A new item was put on a stack, by a buy, a level cue or when the run started.
+    Spawned {
+        item: ItemId,
+        item_type: ItemType,
+        stack: usize,
+    },
+    /// A held item landed on a stack.
+    Stacked {
+        item: ItemId,
+        stack: usize,
+    },
+    Bought,
+    /// A buy that brings `size` items at once.
+    BulkPurchase {
+        size: usize,
+    },
+    Queued {
+        item: ItemId,
+    },
+    Consumed {
+        item: ItemId,
+        item_type: ItemType,
+        variant: Variant,
+    },
+    Stress {
+        value: f32,
+        cause: StressCause,
+    },
+    DayEnded {
+        bought: bool,
+        /// Items on the wrong stack when the day ended.
+        mismatched: usize,
+    },
+    /// The last day of a week ended.
+    WeekEnded,
+    /// The level cue with this index happened.
+    Cue(usize),
+    SaleAnnounced(ItemType),
+    SaleStarted(ItemType),
+    SaleEnded(ItemType),
+    /// The run was lost. Sent once.
+    Failed,
+}
+
+/// Shortcuts to states that take a while to reach, for the debug console.
+/// Whatever they cause is sent with the next step.
+#[cfg(feature = "debug_console")]
+impl Sim {
+    /// Buys `count` items of the run's category at index `category` onto its
+    /// own stack, however full that is.
+    pub fn spawn(&mut self, category: usize, count: usize, rng: &mut impl RngCore) {
+        let mut events = std::mem::take(&mut self.pending);
+        for _ in 0..count {
+            // stacks are in category order
+            self.spawn_on(category, category, rng, &mut events);
+        }
+        self.pending = events;
+    }
+
+    pub fn trigger_bulk(&mut self, rng: &mut impl RngCore) {
+        let mut events = std::mem::take(&mut self.pending);
+        self.bulk_purchase(rng, &mut events);
+        self.pending = events;
+    }
+
+    /// Ends the day with the next step.
+    pub fn skip_day(&mut self) {
+        self.today.elapsed = self.today.duration;
+    }
+
+    /// Consumes the active item, or the first queued one, with the next step.
+    pub fn finish_consuming(&mut self) {
+        self.check_active();
+        if let Some(consuming) = &mut self.active {
+            consuming.elapsed = consuming.duration;
+        }
+    }
+}
+
+/// Inputs collected from picking callbacks until the next [`Sim::step`].
+#[derive(Resource, Default)]
+pub struct SimInputs(pub Vec<SimInput>);
+
+impl SimInputs {
+    pub fn push(&mut self, input: SimInput) {
+        self.0.push(input);
+    }
+}
+
+/// Systems that turn keys, buttons and touches into [`SimInputs`] through
+/// commands. They run before the frame's inputs are recorded and stepped.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PlayerInput;
+
+/// Where an item currently is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Location {
+    Stack(usize),
+    Queue(usize),
+    Active,
+    Held,
+}
+
+/// Where a run is at, everything [`Sim::new`] doesn't build from the rules.
+/// Saved so the run can be picked up later with [`Sim::resume`].
+#[derive(Serialize, Deserialize)]
+pub struct SimState {
+    balance: Balance,
+    stacks: Vec<Vec<Item>>,
+    queue: VecDeque<Item>,
+    active: Option<Consuming>,
+    /// Items in the player's hand. They land on a stack once resumed.
+    held: Vec<Item>,
+    stress: f32,
+    today: TodayTimer,
+    calendar: Calendar,
+    sale: Option<Sale>,
+    consumed: ConsumeCount,
+    elapsed: Duration,
+    next_cue: usize,
+    next_id: u64,
+}
+
+#[derive(Resource)]
+pub struct Sim {
+    pub categories: Vec<CategoryRules>,
+    pub balance: Balance,
+    /// How the rules escalate as the run goes on.
+    pub curve: DifficultyCurve,
+    pub stacks: Vec<SimStack>,
+    pub queue: VecDeque<Item>,
+    pub active: Option<Consuming>,
+    pub held: Vec<Held>,
+    /// Zero up to the balance's fail stress.
+    pub stress: f32,
+    pub stack_penalty: f32,
+    pub today: TodayTimer,
+    pub calendar: Calendar,
+    /// The upcoming or current sale.
+    pub sale: Option<Sale>,
+    pub consumed: ConsumeCount,
+    /// Time the run has been going for.
+    pub elapsed: Duration,
+    /// Set when playing a campaign level.
+    pub level: Option<LevelRules>,
+    next_cue: usize,
+    next_id: u64,
+    /// Events from setting up the run, sent with the first step.
+    pending: Vec<SimEvent>,
+    /// Whether [`SimEvent::Failed`] was sent.
+    failed_sent: bool,
+}
+
+impl Sim {
+    /// Starts a run with one stack per category, each seeded with a few items
+    /// or as many as the level asks for.
+    pub fn new(
+        categories: Vec<CategoryRules>,
+        balance: Balance,
+        curve: DifficultyCurve,
+        level: Option<LevelRules>,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let day_length = balance.day_length * curve.sample(0.).day_length;
+        let mut sim = Self {
+            stacks: categories
+                .iter()
+                .map(|category| SimStack::new(category.item_type))
+                .collect(),
+            categories,
+            balance,
+            curve,
+            queue: VecDeque::new(),
+            active: None,
+            held: Vec::new(),
+            stress: balance.start_stress,
+            stack_penalty: 0.,
+            today: TodayTimer::new(day_length),
+            calendar: Calendar::new(balance.start_stress),
+            sale: None,
+            consumed: ConsumeCount::default(),
+            elapsed: Duration::ZERO,
+            level,
+            next_cue: 0,
+            next_id: 0,
+            pending: Vec::new(),
+            failed_sent: false,
+        };
+
+        // seed the stacks
+        let mut events = Vec::new();
+        for stack in 0..sim.stacks.len() {
+            let count = match &sim.level {
+                Some(level) => level.stacks.get(stack).copied().unwrap_or(0),
+                None => random_usize(rng, balance.min_start_items, balance.max_start_items),
+            };
+            for _ in 0..count {
+                sim.spawn_on(stack, stack, rng, &mut events);
+            }
+        }
+        sim.pending = events;
+        sim.stack_penalty = sim.check_stacks();
+
+        sim
+    }
+
+    pub fn step(
+        &mut self,
+        dt: Duration,
+        inputs: &[SimInput],
+        rng: &mut impl RngCore,
+    ) -> Vec<SimEvent> {
+        let mut events = std::mem::take(&mut self.pending);
+        self.elapsed += dt;
+        for input in inputs {
+            self.apply(*input, rng, &mut events);
+        }
+        self.restack(rng, &mut events);
+        self.stack_penalty = self.check_stacks();
+        self.check_active();
+        self.consume_active(dt, &mut events);
+        self.check_sale(dt, &mut events);
+        self.check_timer(dt, rng, &mut events);
+        self.run_script(rng, &mut events);
+        if !self.failed_sent && self.failed() {
+            self.failed_sent = true;
+            events.push(SimEvent::Failed);
+        }
+
+        events
+    }
+
+    pub fn state(&self) -> SimState {
+        SimState {
+            balance: self.balance,
+            stacks: self
+                .stacks
+                .iter()
+                .map(|stack| stack.items.clone())
+                .collect(),
+            queue: self.queue.clone(),
+            active: self.active,
+            held: self.held.iter().map(|held| held.item).collect(),
+            stress: self.stress,
+            today: self.today,
+            calendar: self.calendar.clone(),
+            sale: self.sale,
+            consumed: self.consumed.clone(),
+            elapsed: self.elapsed,
+            next_cue: self.next_cue,
+            next_id: self.next_id,
+        }
+    }
+
+    /// Picks up a saved run in place of this one, which has to be playing by
+    /// the same rules. Leaves this run alone if the save doesn't fit them.
+    pub fn resume(&mut self, state: SimState) -> Result<(), String> {
+        state.balance.validate().map_err(|e| e.to_string())?;
+        if state.stacks.len() != self.stacks.len() {
+            return Err(format!(
+                "the run has {} stacks, not {}",
+                state.stacks.len(),
+                self.stacks.len()
+            ));
+        }
+        let items = state
+            .stacks
+            .iter()
+            .flatten()
+            .chain(&state.queue)
+            .chain(state.active.as_ref().map(|consuming| &consuming.item))
+            .chain(&state.held);
+        for item in items {
+            self.check_item(item, state.next_id)?;
+        }
+
+        for (stack, items) in self.stacks.iter_mut().zip(state.stacks) {
+            stack.items = items;
+        }
+        self.balance = state.balance;
+        self.queue = state.queue;
+        self.active = state.active;
+        self.held = state
+            .held
+            .into_iter()
+            .map(|item| Held {
+                item,
+                dragging: false,
+            })
+            .collect();
+        self.stress = state.stress;
+        self.today = state.today;
+        self.calendar = state.calendar;
+        self.sale = state.sale;
+        self.consumed = state.consumed;
+        self.elapsed = state.elapsed;
+        self.next_cue = state.next_cue;
+        self.next_id = state.next_id;
+        // the items of the run this replaces were never seen
+        self.pending.clear();
+        self.failed_sent = false;
+        self.stack_penalty = self.check_stacks();
+        Ok(())
+    }
+
+    /// Whether a saved item is one this run could have bought.
+    fn check_item(&self, item: &Item, next_id: u64) -> Result<(), String> {
+        // a level only plays some of the catalog, so look the category up by type
+        let category = self
+            .categories
+            .iter()
+            .find(|category| category.item_type == item.item_type);
+        let Some(category) = category else {
+            return Err(format!("category {} is not in play", item.item_type.0));
+        };
+        let exists = match item.variant {
+            Variant::Catalog(index) => index < category.items.len(),
+            Variant::Generated { blank, .. } => category
+                .generated
+                .is_some_and(|generated| blank < generated.blanks),
+        };
+        if !exists {
+            return Err(format!(
+                "category {} has no item {:?}",
+                item.item_type.0, item.variant
+            ));
+        }
+        if !item.offset.is_finite() || item.offset.abs() > MAX_OFFSET {
+            return Err(format!("item {:?} is offset by {}", item.id, item.offset));
+        }
+        if item.id.0 >= next_id {
+            return Err(format!("item {:?} was never bought", item.id));
+        }
+        Ok(())
+    }
+
+    /// Whether the run is lost, by stress or by missing the level's goal.
+    pub fn failed(&self) -> bool {
+        if self.stress > self.balance.fail_stress {
+            return true;
+        }
+        let missed_goal = match self.level.as_ref().map(|level| level.goal) {
+            Some(Goal::Consume { days, .. }) => self.calendar.day() >= days,
+            Some(Goal::KeepStressUnder { stress, .. }) => self.stress >= stress,
+            None => false,
+        };
+        missed_goal && !self.won()
+    }
+
+    /// Whether the level's goal is met. Endless runs can't be won.
+    pub fn won(&self) -> bool {
+        match self.level.as_ref().map(|level| level.goal) {
+            Some(Goal::Consume { items, .. }) => self.consumed.total >= items,
+            Some(Goal::KeepStressUnder { secs, .. }) => self.elapsed.as_secs_f32() >= secs,
+            None => false,
+        }
+    }
+
+    /// Where the run is on the difficulty curve.
+    pub fn pressure(&self) -> Keyframe {
+        self.curve.sample(match self.curve.key {
+            CurveKey::Elapsed => self.elapsed.as_secs_f32(),
+            CurveKey::Consumed => self.consumed.total as f32,
+        })
+    }
+
+    /// Every item in the run with where it is.
+    pub fn items(&self) -> impl Iterator<Item = (&Item, Location)> {
+        let stacked = self.stacks.iter().enumerate().flat_map(|(i, stack)| {
+            stack
+                .items
+                .iter()
+                .map(move |item| (item, Location::Stack(i)))
+        });
+        let queued = self
+            .queue
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item, Location::Queue(i)));
+        let active = self
+            .active
+            .iter()
+            .map(|consuming| (&consuming.item, Location::Active));
+        let held = self.held.iter().map(|held| (&held.item, Location::Held));
+
+        stacked.chain(queued).chain(active).chain(held)
+    }
+
+    fn apply(&mut self, input: SimInput, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        match input {
+            SimInput::Buy => self.buy(rng, events),
+            SimInput::RemoveFromStack(id) => {
+                for stack in &mut self.stacks {
+                    if let Some(i) = stack.items.iter().position(|item| item.id == id) {
+                        let item = stack.items.remove(i);
+                        self.held.push(Held {
+                            item,
+                            dragging: true,
+                        });
+                        return;
+                    }
+                }
+            }
+            SimInput::AddToStack { item, stack } => {
+                let Some(i) = self.held.iter().position(|held| held.item.id == item) else {
+                    return;
+                };
+                if stack >= self.stacks.len() {
+                    return;
+                }
+                self.add_to_stack(i, stack, rng, events);
+            }
+            SimInput::AddToQueue(id) => {
+                let Some(i) = self.held.iter().position(|held| held.item.id == id) else {
+                    return;
+                };
+                if self.queue.len() >= self.balance.max_queue_items {
+                    return;
+                }
+                let held = self.held.remove(i);
+                self.queue.push_back(held.item);
+                events.push(SimEvent::Queued { item: id });
+            }
+            SimInput::Release(id) => {
+                if let Some(held) = self.held.iter_mut().find(|held| held.item.id == id) {
+                    held.dragging = false;
+                }
+            }
+        }
+    }
+
+    /// Puts the held item at `held` on `stack`, or on a random stack with room
+    /// if that one is full.
+    fn add_to_stack(
+        &mut self,
+        held: usize,
+        stack: usize,
+        rng: &mut impl RngCore,
+        events: &mut Vec<SimEvent>,
+    ) -> bool {
+        let stack = if self.stacks[stack].current_height() < self.balance.max_stack_height {
+            stack
+        } else {
+            let Some(stack) = self.random_stack(rng) else {
+                // there are no free stacks.
+                return false;
+            };
+            stack
+        };
+
+        let held = self.held.remove(held);
+        self.stacks[stack].items.push(held.item);
+        events.push(SimEvent::Stacked {
+            item: held.item.id,
+            stack,
+        });
+        true
+    }
+
+    // if an item was let go without landing anywhere, put it back on a stack
+    fn restack(&mut self, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        let mut i = 0;
+        while i < self.held.len() {
+            if self.held[i].dragging {
+                i += 1;
+                continue;
+            }
+            let rand_stack = random_index(rng, self.stacks.len());
+            if !self.add_to_stack(i, rand_stack, rng, events) {
+                i += 1;
+            }
+        }
+    }
+
+    /// Buys an item from `category` and puts it on `stack`.
+    fn spawn_on(
+        &mut self,
+        category: usize,
+        stack: usize,
+        rng: &mut impl RngCore,
+        events: &mut Vec<SimEvent>,
+    ) {
+        let offset = ((rng.next_u32() as f32 / u32::MAX as f32) - 0.5) * 2. * MAX_OFFSET;
+        let category = &self.categories[category];
+        let weights = category.items.iter().map(|item| item.weight);
+        let generated_weight = category.generated.map(|g| g.rules.weight);
+        let item_index = weighted_index(rng, weights.chain(generated_weight));
+        let (variant, rules) = match category.items.get(item_index) {
+            Some(rules) => (Variant::Catalog(item_index), *rules),
+            None => {
+                // the catalog turns down categories with nothing to buy
+                let Some(generated) = category.generated else {
+                    return;
+                };
+                let variant = Variant::Generated {
+                    blank: random_index(rng, generated.blanks),
+                    title: rng.next_u64(),
+                };
+                (variant, generated.rules)
+            }
+        };
+        let item = Item {
+            id: ItemId(self.next_id),
+            item_type: category.item_type,
+            variant,
+            offset,
+            height: rules.height,
+            consume_time: rules.consume_time,
+        };
+        self.next_id += 1;
+        self.stacks[stack].items.push(item);
+        events.push(SimEvent::Spawned {
+            item: item.id,
+            item_type: item.item_type,
+            stack,
+        });
+    }
+
+    fn random_stack(&self, rng: &mut impl RngCore) -> Option<usize> {
+        let stacks: Vec<usize> = self
+            .stacks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, stack)| {
+                if stack.current_height() > self.balance.max_stack_height {
+                    None
+                } else {
+                    Some(i)
+                }
+            })
+            .collect();
+
+        if stacks.is_empty() {
+            return None;
+        }
+
+        Some(stacks[random_index(rng, stacks.len())])
+    }
+
+    fn spawn_random(&mut self, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) -> bool {
+        let category = random_index(rng, self.categories.len());
+
+        let Some(stack) = self.random_stack(rng) else {
+            return false;
+        };
+        self.spawn_on(category, stack, rng, events);
+
+        true
+    }
+
+    fn buy(&mut self, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        if let Some(sale) = self.sale.filter(Sale::active) {
+            self.buy_on_sale(sale, rng, events);
+            return;
+        }
+
+        let event = (rng.next_u32() as f32 / u32::MAX as f32) < self.pressure().bulk_chance;
+        if event {
+            // don't decrement the stress meter if we haven't bought anything
+            if !self.bulk_purchase(rng, events) {
+                return;
+            }
+        } else if self.spawn_random(rng, events) {
+            self.calendar.today.items_bought += 1;
+        } else {
+            return;
+        }
+
+        self.stress -= self.balance.buy_relief;
+        self.today.clicked_today = true;
+        events.push(SimEvent::Bought);
+        events.push(SimEvent::Stress {
+            value: -self.balance.buy_relief,
+            cause: StressCause::Buy,
+        });
+    }
+
+    /// Buys as many random items at once as the difficulty curve says. Returns
+    /// false when there was no room for any.
+    fn bulk_purchase(&mut self, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) -> bool {
+        let (min, max) = self.pressure().bulk_size;
+        let (min, max) = (min.round() as usize, max.round() as usize);
+        let event_size = random_index(rng, max - min + 1) + min;
+        events.push(SimEvent::BulkPurchase { size: event_size });
+        let mut spawned_one = false;
+        for _ in 0..event_size {
+            if !self.spawn_random(rng, events) {
+                // all stacks are full
+                break;
+            }
+            self.calendar.today.items_bought += 1;
+            spawned_one = true;
+        }
+        spawned_one
+    }
+
+    /// A sale buy brings a few items of the sale category and relieves more stress.
+    fn buy_on_sale(&mut self, sale: Sale, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        let Some(category) = self
+            .categories
+            .iter()
+            .position(|category| category.item_type == sale.item_type)
+        else {
+            return;
+        };
+        let mut spawned_one = false;
+        let count = random_usize(
+            rng,
+            self.balance.min_sale_items,
+            self.balance.max_sale_items,
+        );
+        for _ in 0..count {
+            let Some(stack) = self.random_stack(rng) else {
+                // all stacks are full
+                break;
+            };
+            self.spawn_on(category, stack, rng, events);
+            self.calendar.today.items_bought += 1;
+            spawned_one = true;
+        }
+        if !spawned_one {
+            return;
+        }
+
+        self.stress -= self.balance.sale_relief;
+        self.today.clicked_today = true;
+        events.push(SimEvent::Bought);
+        events.push(SimEvent::Stress {
+            value: -self.balance.sale_relief,
+            cause: StressCause::Buy,
+        });
+    }
+
+    fn check_stacks(&self) -> f32 {
+        self.stacks
+            .iter()
+            .map(|stack| stack.mismatched() as f32 * self.balance.mismatch_penalty)
+            .sum::<f32>()
+            * self.pressure().mismatch_penalty
+    }
+
+    fn check_active(&mut self) {
+        if self.active.is_some() {
+            return;
+        }
+        let Some(item) = self.queue.pop_front() else {
+            return;
+        };
+        let mut scale = self.pressure().consume_time;
+        if self.calendar.is_weekend() {
+            scale *= self.balance.weekend_consume_time;
+        }
+        self.active = Some(Consuming {
+            item,
+            elapsed: Duration::ZERO,
+            duration: item.consume_time.mul_f32(scale),
+        });
+    }
+
+    fn consume_active(&mut self, dt: Duration, events: &mut Vec<SimEvent>) {
+        let Some(ref mut consuming) = self.active else {
+            return;
+        };
+        consuming.elapsed += dt;
+        if consuming.elapsed < consuming.duration {
+            return;
+        }
+
+        let item = consuming.item;
+        self.active = None;
+        self.emit_stress(-self.balance.consume_relief, StressCause::Consume, events);
+        self.consumed.record(item.item_type, item.variant);
+        self.calendar.today.items_consumed += 1;
+        events.push(SimEvent::Consumed {
+            item: item.id,
+            item_type: item.item_type,
+            variant: item.variant,
+        });
+    }
+
+    fn check_sale(&mut self, dt: Duration, events: &mut Vec<SimEvent>) {
+        let Some(sale) = &mut self.sale else {
+            return;
+        };
+        if !sale.active() {
+            sale.starts_in = sale.starts_in.saturating_sub(dt);
+            if sale.active() {
+                events.push(SimEvent::SaleStarted(sale.item_type));
+            }
+            return;
+        }
+        sale.remaining = sale.remaining.saturating_sub(dt);
+        if sale.remaining.is_zero() {
+            events.push(SimEvent::SaleEnded(sale.item_type));
+            self.sale = None;
+        }
+    }
+
+    fn schedule_sale(&mut self, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        if self.sale.is_some() || random_index(rng, self.balance.sale_chance) != 0 {
+            return;
+        }
+        let item_type = self.categories[random_index(rng, self.categories.len())].item_type;
+        self.sale = Some(Sale {
+            item_type,
+            starts_in: Duration::from_secs_f32(self.balance.sale_lead_time),
+            remaining: Duration::from_secs_f32(self.balance.sale_length),
+        });
+        events.push(SimEvent::SaleAnnounced(item_type));
+    }
+
+    fn check_timer(&mut self, dt: Duration, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        if !self.today.tick(dt) {
+            return;
+        }
+
+        let bought = self.today.clicked_today;
+        self.today.clicked_today = false;
+        let click_penalty = if bought {
+            self.balance.bought_penalty
+        } else {
+            self.balance.missed_buy_penalty
+        };
+        let mismatched = self.stacks.iter().map(SimStack::mismatched).sum();
+        events.push(SimEvent::DayEnded { bought, mismatched });
+        self.emit_stress(
+            click_penalty + self.stack_penalty,
+            StressCause::EndOfDay,
+            events,
+        );
+        if self.calendar.end_day(bought, self.stress) {
+            events.push(SimEvent::WeekEnded);
+        }
+
+        let timer_secs = self.balance.day_length * self.pressure().day_length;
+        self.today.duration = Duration::from_secs_f32(timer_secs);
+
+        self.schedule_sale(rng, events);
+    }
+
+    /// Plays every level cue that's due.
+    fn run_script(&mut self, rng: &mut impl RngCore, events: &mut Vec<SimEvent>) {
+        loop {
+            let Some(cue) = self
+                .level
+                .as_ref()
+                .and_then(|level| level.script.get(self.next_cue))
+                .copied()
+            else {
+                return;
+            };
+            if cue.at > self.elapsed {
+                return;
+            }
+            if let CueAction::Buy { category, count } = cue.action {
+                for _ in 0..count {
+                    let Some(stack) = self.random_stack(rng) else {
+                        // all stacks are full
+                        break;
+                    };
+                    self.spawn_on(category, stack, rng, events);
+                    self.calendar.today.items_bought += 1;
+                }
+            }
+            events.push(SimEvent::Cue(self.next_cue));
+            self.next_cue += 1;
+        }
+    }
+
+    fn emit_stress(&mut self, value: f32, cause: StressCause, events: &mut Vec<SimEvent>) {
+        events.push(SimEvent::Stress { value, cause });
+        if value < 0. && self.stress <= 0. {
+            return;
+        }
+        self.stress += value;
+    }
+}
+
+pub fn random_usize(rng: &mut impl RngCore, min: usize, max: usize) -> usize {
+    let range = max - min;
+    ((rng.next_u64() as f64 / u64::MAX as f64) * range as f64 - 0.5).round() as usize + min
+}
+
+/// Random index into something `len` long.
+pub fn random_index(rng: &mut impl RngCore, len: usize) -> usize {
+    let i = ((rng.next_u32() as f64 / u32::MAX as f64) * len as f64 - 0.5).round() as usize;
+    i.min(len.saturating_sub(1))
+}
+
+/// Random index where each entry is picked in proportion to its weight.
+pub fn weighted_index(rng: &mut impl RngCore, weights: impl Iterator<Item = f32> + Clone) -> usize {
+    let total: f32 = weights.clone().sum();
+    let mut roll = rng.next_u32() as f32 / u32::MAX as f32 * total;
+    let mut last = 0;
+    for (i, weight) in weights.enumerate() {
+        if roll < weight {
+            return i;
+        }
+        roll -= weight;
+        last = i;
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_rand::prelude::ChaCha8Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    /// A day of the default balance.
+    const DAY: Duration = Duration::from_secs(10);
+
+    /// An endless run with two categories of one item each, on a flat curve.
+    fn sim(seed: u64) -> (Sim, ChaCha8Rng) {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let categories = (0..2)
+            .map(|i| CategoryRules {
+                item_type: ItemType(i),
+                items: vec![ItemRules {
+                    height: 10.,
+                    consume_time: Duration::from_secs(1),
+                    weight: 1.,
+                }],
+                generated: None,
+            })
+            .collect();
+        let curve = DifficultyCurve {
+            key: CurveKey::Elapsed,
+            keyframes: vec![Keyframe {
+                at: 0.,
+                day_length: 1.,
+                bulk_chance: 0.,
+                bulk_size: (1., 1.),
+                consume_time: 1.,
+                mismatch_penalty: 1.,
+            }],
+        };
+        let sim = Sim::new(categories, Balance::NORMAL, curve, None, &mut rng);
+        (sim, rng)
+    }
+
+    fn top(sim: &Sim, stack: usize) -> ItemId {
+        sim.stacks[stack].items.last().unwrap().id
+    }
+
+    #[test]
+    fn day_end_adds_the_missed_or_bought_penalty() {
+        let (mut missed, mut rng) = sim(1);
+        let events = missed.step(DAY, &[], &mut rng);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SimEvent::DayEnded {
+                bought: false,
+                mismatched: 0
+            }
+        )));
+        let balance = Balance::NORMAL;
+        assert_eq!(
+            missed.stress,
+            balance.start_stress + balance.missed_buy_penalty
+        );
+
+        let (mut bought, mut rng) = sim(1);
+        bought.step(DAY, &[SimInput::Buy], &mut rng);
+        // the bought item may have landed on the wrong stack
+        let expected = balance.start_stress - balance.buy_relief
+            + balance.bought_penalty
+            + bought.stack_penalty;
+        assert_eq!(bought.stress, expected);
+    }
+
+    #[test]
+    fn queue_takes_no_more_than_the_limit() {
+        let (mut sim, mut rng) = sim(2);
+        let items: Vec<ItemId> = sim.stacks[0]
+            .items
+            .iter()
+            .rev()
+            .take(3)
+            .map(|item| item.id)
+            .collect();
+        let inputs: Vec<SimInput> = items
+            .iter()
+            .flat_map(|id| {
+                [
+                    SimInput::RemoveFromStack(*id),
+                    SimInput::AddToQueue(*id),
+                    SimInput::Release(*id),
+                ]
+            })
+            .collect();
+        let events = sim.step(Duration::ZERO, &inputs, &mut rng);
+        let queued = events
+            .iter()
+            .filter(|event| matches!(event, SimEvent::Queued { .. }))
+            .count();
+        assert_eq!(queued, Balance::NORMAL.max_queue_items);
+        // the one that didn't fit went back on a stack
+        let location = sim.items().find(|(item, _)| item.id == items[2]).unwrap().1;
+        assert!(matches!(location, Location::Stack(_)));
+    }
+
+    #[test]
+    fn items_on_the_wrong_stack_add_the_mismatch_penalty() {
+        let (mut sim, mut rng) = sim(3);
+        let item = top(&sim, 0);
+        let inputs = [
+            SimInput::RemoveFromStack(item),
+            SimInput::AddToStack { item, stack: 1 },
+        ];
+        sim.step(Duration::ZERO, &inputs, &mut rng);
+        let penalty = Balance::NORMAL.mismatch_penalty;
+        assert_eq!(sim.stack_penalty, penalty);
+
+        let stress = sim.stress;
+        sim.step(DAY, &[], &mut rng);
+        assert_eq!(
+            sim.stress,
+            stress + Balance::NORMAL.missed_buy_penalty + penalty
+        );
+    }
+
+    #[test]
+    fn same_seed_and_inputs_play_the_same() {
+        let play = |seed| {
+            let (mut sim, mut rng) = sim(seed);
+            let mut log = Vec::new();
+            for frame in 0..200 {
+                let inputs = match frame % 20 {
+                    0 => vec![SimInput::Buy],
+                    10 => match sim.stacks.iter().find_map(|stack| stack.items.last()) {
+                        Some(item) => vec![
+                            SimInput::RemoveFromStack(item.id),
+                            SimInput::AddToQueue(item.id),
+                        ],
+                        None => Vec::new(),
+                    },
+                    _ => Vec::new(),
+                };
+                let events = sim.step(Duration::from_millis(100), &inputs, &mut rng);
+                log.push(format!("{events:?}"));
+            }
+            let stacks: Vec<Vec<ItemId>> = (0..2)
+                .map(|i| sim.stacks[i].items.iter().map(|item| item.id).collect())
+                .collect();
+            (log, stacks, sim.stress)
+        };
+        assert_eq!(play(4), play(4));
+    }
+}
@@ -1,35 +1,9 @@
-use std::{f32::consts::PI, time::Duration};
+use std::f32::consts::PI;
 
-use bevy::{
-    audio::{Volume, VolumeLevel, PlaybackMode},
-    prelude::*,
-    sprite::MaterialMesh2dBundle,
-};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use bevy_mod_picking::prelude::*;
 
-use crate::{
-    consume_counter::ConsumeCount,
-    layers,
-    stack::{SpawnEvent, StackPenalty},
-    stress::{EmitStress, StressPopupText},
-    Sfx,
-};
-
-pub struct SpawningPlugin;
-impl Plugin for SpawningPlugin {
-    fn build(&self, app: &mut App) {
-        app.insert_resource(TodayTimer {
-            timer: Timer::from_seconds(10., TimerMode::Repeating),
-            clicked_today: false,
-        });
-    }
-}
-
-#[derive(Resource)]
-pub struct TodayTimer {
-    timer: Timer,
-    pub clicked_today: bool,
-}
+use crate::{layers, settings::Settings, sim::Sim, stack::SpawnEvent};
 
 #[derive(Component)]
 pub struct CircleButton;
@@ -48,13 +22,15 @@ pub fn spawn_button(
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
 ) {
     commands
         .spawn((
             CircleButton,
             SpriteBundle {
                 sprite: Sprite {
-                    custom_size: Some(Vec2::splat(34.)),
+                    // the hit area, a bit bigger than the art so it's easy to tap
+                    custom_size: Some(Vec2::splat(56.)),
                     color: Color::WHITE.with_a(0.),
                     ..default()
                 },
@@ -67,8 +43,8 @@ pub fn spawn_button(
             }),
         ))
         .with_children(|children| {
-            let bad_material = materials.add(ColorMaterial::from(Color::CRIMSON));
-            let happy_material = materials.add(ColorMaterial::from(Color::WHITE));
+            let bad_material = materials.add(ColorMaterial::from(settings.palette.warning()));
+            let happy_material = materials.add(ColorMaterial::from(settings.palette.done()));
             children
                 .spawn((
                     BuyClockHand {
@@ -112,54 +88,27 @@ pub fn spawn_button(
         });
 }
 
-pub fn check_timer(
-    mut commands: Commands,
-    mut today: ResMut<TodayTimer>,
-    time: Res<Time>,
-    stack_penalty: Res<StackPenalty>,
-    sfx: Res<Sfx>,
-    button: Query<&GlobalTransform, With<CircleButton>>,
-    consumed_counter: Res<ConsumeCount>,
-) {
-    if today.timer.tick(time.delta()).finished() {
-        let click_penalty = if today.clicked_today {
-            today.clicked_today = false;
-            2.
-        } else {
-            commands.spawn(AudioBundle {
-                source: sfx.no_click.clone(),
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Despawn,
-                    volume: Volume::Relative(VolumeLevel::new(0.5)),
-                    ..default()
-                },
-            });
-            5.
-        };
-        let stress_value = click_penalty + stack_penalty.0;
-        commands.add(EmitStress(stress_value));
-        commands.add(StressPopupText {
-            spawn_origin: button.single().translation() - 35. * Vec3::X + 100. * Vec3::Z,
-            stress_value,
-        });
-
-        // adjust timer time.
-        let timer_secs = 10. - (consumed_counter.total / 10).min(5) as f32;
-        today
-            .timer
-            .set_duration(Duration::from_secs_f32(timer_secs));
-    }
-}
-
 pub fn draw_button(
-    today: Res<TodayTimer>,
+    sim: Res<Sim>,
+    settings: Res<Settings>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
     mut q: Query<(&mut Transform, &BuyClockHand, &mut Handle<ColorMaterial>)>,
 ) {
-    let fraction_left = today.timer.elapsed_secs() / today.timer.duration().as_secs_f32();
+    let today = &sim.today;
+    let fraction_left = today.fraction();
 
     for (mut t, materials, mut handle) in &mut q {
         t.rotation = Quat::from_rotation_z(-2. * PI * fraction_left);
 
+        if settings.is_changed() {
+            if let Some(bad) = color_materials.get_mut(&materials.bad_material) {
+                bad.color = settings.palette.warning();
+            }
+            if let Some(happy) = color_materials.get_mut(&materials.happy_material) {
+                happy.color = settings.palette.done();
+            }
+        }
+
         *handle = if today.clicked_today {
             materials.happy_material.clone()
         } else {
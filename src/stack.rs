@@ -1,58 +1,61 @@
 use bevy::{
-    ecs::system::{Command, EntityCommand, SystemState},
+    ecs::system::{Command, EntityCommand},
     prelude::*,
     sprite::Anchor,
 };
 use bevy_mod_picking::prelude::*;
-use bevy_rand::prelude::*;
-use rand_core::RngCore;
 
 use crate::{
-    dialog::{DialogBox, ShownDialog},
-    item::{ItemBundle, ItemDragging, ItemHandleIndex, ItemHandles, ItemType},
+    accessibility::MismatchGlyph,
+    catalog::{CatalogCategory, ItemCatalog},
+    item::ItemEntities,
     layers,
-    queue::{ActiveItem, InQueue},
-    spawning::TodayTimer,
-    stress::{StressMeter, StressPopupText},
-    Sfx,
+    locale::Locale,
+    settings::Settings,
+    sim::{ItemId, Sim, SimInput, SimInputs},
+    touch::is_touch,
 };
 
+/// Sprite for the [`SimStack`](crate::sim::SimStack) at `index`.
 #[derive(Component, Default)]
 pub struct Stack {
-    item_type: ItemType,
-    items: Vec<Entity>,
-    current_height: f32,
+    pub index: usize,
 }
 
 impl Stack {
-    const MAX_HEIGHT: f32 = 240.;
-    fn new(item_type: ItemType) -> Self {
-        Self {
-            item_type,
-            items: Vec::new(),
-            current_height: 0.,
-        }
-    }
+    /// Horizontal space the stacks are spread across.
+    const AREA_WIDTH: f32 = 500.;
+    /// Room between the widest items of neighbouring stacks.
+    const GAP: f32 = 60.;
+    /// Height of the category label box, below the bottom of the stack.
+    const LABEL_Y: f32 = -12.;
+    /// Width of the category label box art.
+    const LABEL_WIDTH: f32 = 42.;
+    /// Width of the sale icon art.
+    const SALE_ICON_WIDTH: f32 = 21.;
 
     pub fn spawn(
         commands: &mut Commands,
         transform: Transform,
-        item_type: ItemType,
+        index: usize,
+        size: Vec2,
+        category: &CatalogCategory,
         asset_server: &AssetServer,
+        locale: &Locale,
     ) -> Entity {
         commands
             .spawn((
                 SpriteBundle {
                     sprite: Sprite {
                         color: Color::CYAN.with_a(0.),
-                        custom_size: Some(Vec2::new(100., Self::MAX_HEIGHT)),
+                        custom_size: Some(size),
                         anchor: Anchor::BottomCenter,
                         ..default()
                     },
                     transform,
                     ..default()
                 },
-                Stack::new(item_type),
+                Stack { index },
                 PickableBundle {
                     pickable: Pickable {
                         should_block_lower: true,
@@ -61,6 +64,10 @@ impl Stack {
                     ..default()
                 },
                 On::<Pointer<Drop>>::commands_mut(move |event, commands| {
+                    // touch drops snap to the closest target instead
+                    if is_touch(event.pointer_id) {
+                        return;
+                    }
                     let Some(ref mut entity_commands) = commands.get_entity(event.dropped) else {
                         return;
                     };
@@ -71,400 +78,229 @@ impl Stack {
                 children
                     .spawn(SpriteBundle {
                         sprite: Sprite {
-                            color: Color::rgb_u8(217, 155, 150),
+                            color: category.color,
                             ..default()
                         },
                         texture: asset_server.load("category_box.png"),
-                        transform: Transform::from_xyz(0., -12., 0.5),
+                        transform: Transform::from_xyz(0., Self::LABEL_Y, 0.5),
                         ..default()
                     })
                     .with_children(|children| {
-                        children.spawn(Text2dBundle {
-                            text: Text::from_section(
-                                item_type.label(),
-                                TextStyle {
-                                    font: asset_server.load("chevyray_bird_seed.ttf"),
-                                    font_size: 10.,
-                                    color: Color::BLACK,
-                                },
-                            ),
-                            transform: Transform::from_xyz(0., -1., 1.0),
-                            ..default()
-                        });
+                        children.spawn((
+                            StackLabel { stack: index },
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    category.name(locale),
+                                    TextStyle {
+                                        font: locale.font.clone(),
+                                        font_size: 10.,
+                                        color: Color::BLACK,
+                                    },
+                                ),
+                                transform: Transform::from_xyz(0., -1., 1.0),
+                                ..default()
+                            },
+                        ));
                     });
+
+                children.spawn((
+                    MismatchGlyph { stack: index },
+                    Text2dBundle {
+                        text: Text::from_section(
+                            "!",
+                            TextStyle {
+                                font: locale.font.clone(),
+                                font_size: 20.,
+                                ..default()
+                            },
+                        ),
+                        transform: Transform::from_xyz(-29., -11., 1.),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ));
+
+                // right of the label, or as far right as the stack's own width allows
+                let sale_x = ((Self::LABEL_WIDTH + Self::SALE_ICON_WIDTH) / 2.)
+                    .min((size.x - Self::SALE_ICON_WIDTH) / 2.);
+                children.spawn((
+                    SaleIcon { stack: index },
+                    SpriteBundle {
+                        texture: asset_server.load("Sale_icon.png"),
+                        transform: Transform::from_xyz(sale_x, Self::LABEL_Y, 1.),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ));
             })
             .id()
     }
 
+    /// Spreads one stack per category evenly across the play area.
     pub fn spawn_stacks(
         commands: &mut Commands,
         asset_server: &AssetServer,
-        rng: &mut GlobalEntropy<ChaCha8Rng>,
+        locale: &Locale,
+        sim: &Sim,
+        catalog: &ItemCatalog,
     ) {
         let stack_y = -54.;
-        let book_id = Stack::spawn(
-            commands,
-            Transform::from_xyz(-187., stack_y, layers::BACKGROUND + 0.1),
-            ItemType::Book,
-            asset_server,
-        );
-
-        let movie_id = Stack::spawn(
-            commands,
-            Transform::from_xyz(-65., stack_y, layers::BACKGROUND + 0.1),
-            ItemType::Movie,
-            asset_server,
-        );
-
-        let game_id = Stack::spawn(
-            commands,
-            Transform::from_xyz(65., stack_y, layers::BACKGROUND + 0.1),
-            ItemType::Game,
-            asset_server,
-        );
-
-        let comic_id = Stack::spawn(
-            commands,
-            Transform::from_xyz(187., stack_y, layers::BACKGROUND + 0.1),
-            ItemType::Comic,
-            asset_server,
-        );
-
-        // seed the stacks
-        for _ in 0..random_usize(rng, 3, 8) {
-            commands.add(SpawnOn {
-                item_type: ItemType::Book,
-                stack_entity: book_id,
-            });
-        }
-
-        for _ in 0..random_usize(rng, 3, 8) {
-            commands.add(SpawnOn {
-                item_type: ItemType::Comic,
-                stack_entity: comic_id,
-            });
-        }
-
-        for _ in 0..random_usize(rng, 3, 8) {
-            commands.add(SpawnOn {
-                item_type: ItemType::Game,
-                stack_entity: game_id,
-            });
-        }
-
-        for _ in 0..random_usize(rng, 3, 8) {
-            commands.add(SpawnOn {
-                item_type: ItemType::Movie,
-                stack_entity: movie_id,
-            });
+        let widest = catalog
+            .categories
+            .iter()
+            .map(|category| category.dimensions.x)
+            .fold(0., f32::max);
+        let count = sim.stacks.len() as f32;
+        let spacing = (widest + Self::GAP).min(Self::AREA_WIDTH / count);
+        for (index, stack) in sim.stacks.iter().enumerate() {
+            let x = (index as f32 - (count - 1.) / 2.) * spacing;
+            Stack::spawn(
+                commands,
+                Transform::from_xyz(x, stack_y, layers::BACKGROUND + 0.1),
+                index,
+                Vec2::new(spacing - 25., sim.balance.max_stack_height),
+                catalog.category(stack.item_type),
+                asset_server,
+                locale,
+            );
         }
     }
 }
 
-fn random_usize(rng: &mut GlobalEntropy<ChaCha8Rng>, min: usize, max: usize) -> usize {
-    let range = max - min;
-    ((rng.next_u64() as f64 / u64::MAX as f64) * range as f64 - 0.5).round() as usize + min
-}
-
-/// Put component on an item to label that it's on a stack
-#[derive(Component)]
-pub struct InStack(pub Entity);
-
-/// Offset when item is in the stack
-#[derive(Component)]
-pub struct StackOffset(pub f32);
-
-struct AddToStack(pub Entity);
+pub struct AddToStack(pub Entity);
 impl EntityCommand for AddToStack {
     fn apply(self, id: Entity, world: &mut World) {
-        let e = world.entity(id);
-        if !e.contains::<ItemType>() || e.contains::<InQueue>() || e.contains::<ActiveItem>() {
-            return;
-        }
-
-        let mut stack = world.query::<&Stack>();
-        let Ok(stack) = stack.get_mut(world, self.0) else {
-            dbg!("could not find stack");
+        let Some(item) = world.get::<ItemId>(id).copied() else {
             return;
         };
-        let stack_entity = if stack.current_height < Stack::MAX_HEIGHT {
-            self.0
-        } else {
-            let Some(e) = get_random_stack(world) else {
-                // there are no free stacks.
-                return;
-            };
-            e
-        };
-
-        let mut stack = world.query::<&mut Stack>();
-        let Ok(mut stack) = stack.get_mut(world, stack_entity) else {
-            dbg!("could not find stack");
+        let Some(stack) = world.get::<Stack>(self.0) else {
+            warn!(
+                "could not find the stack {:?} an item was dropped on",
+                self.0
+            );
             return;
         };
-        stack.items.push(id);
-        stack_item(world, id, stack_entity);
+        let stack = stack.index;
+        world
+            .resource_mut::<SimInputs>()
+            .push(SimInput::AddToStack { item, stack });
     }
 }
 
-pub struct SpawnOn {
-    pub item_type: ItemType,
-    pub stack_entity: Entity,
-}
-
-impl Command for SpawnOn {
-    fn apply(self, world: &mut World) {
-        let mut system_state = SystemState::<(
-            Commands,
-            Res<ItemHandles>,
-            Query<&mut Stack>,
-            ResMut<GlobalEntropy<ChaCha8Rng>>,
-        )>::new(world);
-        let (mut commands, handles, mut query, mut rng) = system_state.get_mut(world);
-        let Ok(mut stack) = query.get_mut(self.stack_entity) else {
-            return;
-        };
-
-        let offset = ((rng.next_u32() as f32 / u32::MAX as f32) - 0.5) * 7.;
-        let item_total = self.item_type.get_handles_max(&handles);
-        let item_index =
-            ((rng.next_u32() as f64 / u32::MAX as f64) * item_total as f64 - 0.5).round() as usize;
-        let new_item = commands
-            .spawn(ItemBundle::new(
-                self.item_type,
-                self.item_type.get_stack_handle(&handles, item_index),
-                offset,
-                item_index,
-                self.stack_entity,
-            ))
-            .id();
-        stack.items.push(new_item);
-        stack.current_height += self.item_type.stack_dimensions().y;
-        system_state.apply(world);
-    }
-}
-
-fn get_random_stack(world: &mut World) -> Option<Entity> {
-    let mut stacks = world.query::<(Entity, &Stack)>();
-    let stacks: Vec<Entity> = stacks
-        .iter(world)
-        .filter_map(|(e, stack)| {
-            if stack.current_height > Stack::MAX_HEIGHT {
-                None
-            } else {
-                Some(e)
-            }
-        })
-        .collect();
-
-    if stacks.is_empty() {
-        return None;
-    }
-
-    let mut r = world.resource_mut::<GlobalEntropy<ChaCha8Rng>>();
-    let stack =
-        ((r.next_u32() as f32 / u32::MAX as f32) * stacks.len() as f32 - 0.5).round() as usize;
-
-    Some(stacks[stack])
-}
-
 pub struct SpawnEvent;
 
-impl SpawnEvent {
-    fn spawn_random(world: &mut World) -> bool {
-        let mut r = world.resource_mut::<GlobalEntropy<ChaCha8Rng>>();
-        let category = ((r.next_u32() as f32 / u32::MAX as f32) * 4.).trunc() as u32;
-        let item_type = match category {
-            0 => ItemType::Book,
-            1 => ItemType::Comic,
-            2 => ItemType::Game,
-            3 | 4 => ItemType::Movie,
-            _ => unreachable!(),
-        };
-
-        let Some(stack_entity) = get_random_stack(world) else {
-            return false;
-        };
-        SpawnOn::apply(
-            SpawnOn {
-                item_type,
-                stack_entity,
-            },
-            world,
-        );
-
-        true
-    }
-}
-
 impl Command for SpawnEvent {
     fn apply(self, world: &mut World) {
-        let mut r = world.resource_mut::<GlobalEntropy<ChaCha8Rng>>();
-        let event = ((r.next_u32() as f32 / u32::MAX as f32) * 10. - 0.5).round() as usize == 1;
-        if event {
-            let event_size =
-                ((r.next_u32() as f32 / u32::MAX as f32) * 5. - 0.5).round() as usize + 4;
-            let mut spawned_one = false;
-            for _ in 0..event_size {
-                let mut r = world.resource_mut::<GlobalEntropy<ChaCha8Rng>>();
-                let dialog = ShownDialog::new_random(&mut r);
-                let mut dialog_box = world.query::<&mut DialogBox>();
-                dialog_box.single_mut(world).timer.reset();
-                world.insert_resource(dialog);
-                if !Self::spawn_random(world) {
-                    // all stacks are full
-                    break;
-                }
-                spawned_one = true;
-            }
-            // don't decrement the stress meter if we haven't bought anything
-            if !spawned_one {
-                return;
-            }
-        } else if !Self::spawn_random(world) {
-            return;
-        }
-
-        let mut stress_meter = world.query::<&mut StressMeter>();
-        stress_meter.single_mut(world).value -= 1.;
-
-        world.resource_mut::<TodayTimer>().clicked_today = true;
-
-        let source = world.resource::<Sfx>().buy.clone();
-        world.spawn(AudioBundle {
-            source,
-            settings: PlaybackSettings::DESPAWN,
-        });
-        StressPopupText::apply(
-            StressPopupText {
-                spawn_origin: Vec3::new(255., 149., 200.),
-                stress_value: -1.,
-            },
-            world,
-        );
+        world.resource_mut::<SimInputs>().push(SimInput::Buy);
     }
 }
 
-fn stack_item(world: &mut World, id: Entity, stack: Entity) -> ItemType {
-    let e = world.entity(id);
-    let t = *e.get::<ItemType>().unwrap();
-    let index = *e.get::<ItemHandleIndex>().unwrap();
-    let handles = world.resource::<ItemHandles>();
-    let new_handle = t.get_stack_handle(handles, index.0);
-    let mut e = world.entity_mut(id);
-    e.insert(InStack(stack));
-    *e.get_mut::<Handle<Image>>().unwrap() = new_handle;
-    e.get_mut::<Sprite>().unwrap().anchor = Anchor::BottomCenter;
-    e.get_mut::<Transform>().unwrap().translation.z = layers::ITEMS;
-
-    t
-}
-
 pub fn stack_items(
-    mut stacks: Query<(&mut Stack, &Transform), Changed<Stack>>,
-    mut items: Query<(&mut Transform, &StackOffset, &ItemType), (With<InStack>, Without<Stack>)>,
+    sim: Res<Sim>,
+    entities: Res<ItemEntities>,
+    stacks: Query<(&Stack, &Transform)>,
+    mut items: Query<&mut Transform, (With<ItemId>, Without<Stack>)>,
 ) {
-    for (mut stack, transform) in &mut stacks {
+    for (stack, transform) in &stacks {
+        let Some(sim_stack) = sim.stacks.get(stack.index) else {
+            continue;
+        };
         let mut current_height = 0.;
-        for entity in stack.items.iter() {
-            let Ok((mut t, x_offset, item_type)) = items.get_mut(*entity) else {
+        for item in sim_stack.items.iter() {
+            let Some(mut t) = entities
+                .0
+                .get(&item.id)
+                .and_then(|e| items.get_mut(*e).ok())
+            else {
                 continue;
             };
-            t.translation = (transform.translation.xy() + Vec2::new(x_offset.0, current_height))
+            t.translation = (transform.translation.xy() + Vec2::new(item.offset, current_height))
                 .extend(layers::ITEMS);
-            current_height += item_type.stack_dimensions().y;
+            current_height += item.height;
         }
-        stack.current_height = current_height;
     }
 }
 
 pub struct RemoveFromStack;
 impl EntityCommand for RemoveFromStack {
     fn apply(self, id: Entity, world: &mut World) {
-        let e = world.entity(id);
-        if !e.contains::<InStack>() {
-            return;
-        }
-        let t = *e.get::<ItemType>().unwrap();
-        let InStack(stack_id) = *e.get::<InStack>().unwrap();
-        let mut query = world.query::<&mut Stack>();
-        let Ok(mut stack) = query.get_mut(world, stack_id) else {
+        let Some(item) = world.get::<ItemId>(id).copied() else {
             return;
         };
+        world
+            .resource_mut::<SimInputs>()
+            .push(SimInput::RemoveFromStack(item));
+    }
+}
 
-        let i = stack.items.iter().position(|e| *e == id).unwrap();
-        stack.items.remove(i);
+/// Tag next to a stack's label while its category is on sale. Shown faded
+/// once the sale is announced.
+#[derive(Component)]
+pub struct SaleIcon {
+    stack: usize,
+}
 
-        let handles = world.resource::<ItemHandles>();
-        let item_index = world.entity(id).get::<ItemHandleIndex>().unwrap();
-        let new_handle = t.get_queue_handle(handles, item_index.0);
-        let mut e = world.entity_mut(id);
-        e.remove::<InStack>();
-        *e.get_mut::<Handle<Image>>().unwrap() = new_handle;
-        e.get_mut::<Sprite>().unwrap().anchor = Anchor::Center;
+impl SaleIcon {
+    pub fn update(sim: Res<Sim>, mut icons: Query<(&SaleIcon, &mut Visibility, &mut Sprite)>) {
+        for (icon, mut visibility, mut sprite) in &mut icons {
+            let sale = sim.sale.filter(|sale| {
+                sim.stacks.get(icon.stack).map(|s| s.item_type) == Some(sale.item_type)
+            });
+            let Some(sale) = sale else {
+                *visibility = Visibility::Hidden;
+                continue;
+            };
+            *visibility = Visibility::Visible;
+            sprite.color.set_a(if sale.active() { 1. } else { 0.4 });
+        }
     }
 }
 
-// if an item is not in queue or stack, put it back in the stack
-pub fn restack(
-    mut commands: Commands,
-    free_items: Query<
-        Entity,
-        (
-            With<ItemType>,
-            Without<InStack>,
-            Without<InQueue>,
-            Without<ItemDragging>,
-            Without<ActiveItem>,
-        ),
-    >,
-    stacks: Query<Entity, With<Stack>>,
-    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
-) {
-    let stacks: Vec<Entity> = stacks.iter().collect();
-    for e in &free_items {
-        let rand_stack = (4. * rng.next_u32() as f32 / u32::MAX as f32 - 0.5).round() as usize;
-        commands.entity(e).add(AddToStack(stacks[rand_stack]));
-    }
+/// Category name on a stack's label box.
+#[derive(Component)]
+pub struct StackLabel {
+    stack: usize,
 }
 
-#[derive(Resource)]
-pub struct StackPenalty(pub f32);
+impl StackLabel {
+    /// Renames the stacks when the language changes mid-run.
+    pub fn update(
+        sim: Res<Sim>,
+        catalog: Res<ItemCatalog>,
+        locale: Res<Locale>,
+        mut labels: Query<(&StackLabel, &mut Text)>,
+    ) {
+        for (label, mut text) in &mut labels {
+            if let Some(stack) = sim.stacks.get(label.stack) {
+                text.sections[0].value = catalog.category(stack.item_type).name(&locale);
+            }
+        }
+    }
+}
 
+/// Turns the category box red when a stack holds items of another category.
 pub fn check_stack(
+    sim: Res<Sim>,
+    catalog: Res<ItemCatalog>,
+    settings: Res<Settings>,
     stacks: Query<(&Stack, &Children)>,
-    items_query: Query<&ItemType, With<InStack>>,
     mut sprites: Query<&mut Sprite>,
-    mut stack_penalty: ResMut<StackPenalty>,
 ) {
-    let mut penalty = 0.;
-    for (
-        Stack {
-            item_type, items, ..
-        },
-        children,
-    ) in &stacks
-    {
-        let rect_entity = children.iter().next();
-        let mut text_stack_has_penalty = false;
-        for item in items.iter() {
-            if items_query.get(*item).unwrap() != item_type {
-                text_stack_has_penalty = true;
-                penalty += 0.5;
-            }
-        }
-
-        if let Some(rect_entity) = rect_entity {
-            let Ok(ref mut sprite) = sprites.get_mut(*rect_entity) else {
-                continue;
-            };
+    for (stack, children) in &stacks {
+        let Some(sim_stack) = sim.stacks.get(stack.index) else {
+            continue;
+        };
+        let Some(rect_entity) = children.iter().next() else {
+            continue;
+        };
+        let Ok(ref mut sprite) = sprites.get_mut(*rect_entity) else {
+            continue;
+        };
 
-            sprite.color = if text_stack_has_penalty {
-                Color::CRIMSON
-            } else {
-                Color::rgb_u8(217, 155, 150)
-            };
-        }
+        sprite.color = if sim_stack.mismatched() > 0 {
+            settings.palette.warning()
+        } else {
+            catalog.category(sim_stack.item_type).color
+        };
     }
-    stack_penalty.0 = penalty;
 }
@@ -1,12 +1,44 @@
 use bevy::prelude::*;
 
-use crate::game_state::GameState;
+use crate::{
+    campaign::{Campaign, CurrentLevel},
+    difficulty::Difficulty,
+    game_state::GameState,
+    high_scores::HighScores,
+    locale::{Locale, Localized},
+    saved_run::SavedRun,
+    seed::RunSeed,
+    settings::{OpenSettingsButton, SettingsState},
+};
 
 pub struct StartScreenPlugin;
 impl Plugin for StartScreenPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::StartScreen), spawn_startup_screen)
-            .add_systems(Update, (button_system, input_start))
+            .add_systems(
+                Update,
+                (button_system, input_start).run_if(
+                    (in_state(GameState::StartScreen).or_else(in_state(GameState::Failed)))
+                        .and_then(in_state(SettingsState::Closed)),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    type_seed,
+                    SeedText::update,
+                    DifficultyButton::handle_clicks,
+                    cycle_difficulty,
+                    DifficultyText::update,
+                    CampaignButton::handle_clicks,
+                    AchievementsButton::handle_clicks,
+                    ContinueButton::handle_clicks,
+                    ScoresText::update,
+                )
+                    .run_if(
+                        in_state(GameState::StartScreen).and_then(in_state(SettingsState::Closed)),
+                    ),
+            )
             .add_systems(OnExit(GameState::StartScreen), despawn_menu);
     }
 }
@@ -14,7 +46,16 @@ impl Plugin for StartScreenPlugin {
 #[derive(Component)]
 pub struct MenuMarker;
 
-fn spawn_startup_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Clicking this starts a run.
+#[derive(Component)]
+pub struct StartButton;
+
+fn spawn_startup_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scores: Res<HighScores>,
+    locale: Res<Locale>,
+) {
     commands
         .spawn((
             MenuMarker,
@@ -26,11 +67,12 @@ fn spawn_startup_screen(mut commands: Commands, asset_server: Res<AssetServer>)
         .with_children(|children| {
             children.spawn((
                 MenuMarker,
+                Localized("start-click"),
                 Text2dBundle {
                     text: Text::from_section(
-                        "Click to Start",
+                        locale.get("start-click"),
                         TextStyle {
-                            font: asset_server.load("chevyray_bird_seed.ttf"),
+                            font: locale.font.clone(),
                             font_size: 16.,
                             color: Color::rgb(0.9, 0.9, 0.9),
                         },
@@ -39,6 +81,22 @@ fn spawn_startup_screen(mut commands: Commands, asset_server: Res<AssetServer>)
                     ..default()
                 },
             ));
+            children.spawn((
+                MenuMarker,
+                SeedText,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: locale.font.clone(),
+                            font_size: 10.,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., -162., 1.),
+                    ..default()
+                },
+            ));
         });
 
     commands
@@ -58,6 +116,7 @@ fn spawn_startup_screen(mut commands: Commands, asset_server: Res<AssetServer>)
         .with_children(|parent| {
             parent.spawn((
                 MenuMarker,
+                StartButton,
                 ButtonBundle {
                     style: Style {
                         width: Val::Percent(100.0),
@@ -71,6 +130,194 @@ fn spawn_startup_screen(mut commands: Commands, asset_server: Res<AssetServer>)
                 },
             ));
         });
+
+    commands
+        .spawn((
+            MenuMarker,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(4.),
+                    top: Val::Px(4.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba_u8(102, 45, 60, 230).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MenuMarker,
+                ScoresText,
+                TextBundle::from_section(
+                    scores.table(&locale),
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+        });
+
+    commands
+        .spawn((
+            MenuMarker,
+            OpenSettingsButton,
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.),
+                    top: Val::Px(4.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba_u8(102, 45, 60, 230).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MenuMarker,
+                Localized("start-settings"),
+                TextBundle::from_section(
+                    locale.get("start-settings"),
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+        });
+
+    commands
+        .spawn((
+            MenuMarker,
+            DifficultyButton,
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.),
+                    top: Val::Px(26.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba_u8(102, 45, 60, 230).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MenuMarker,
+                DifficultyText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+        });
+
+    commands
+        .spawn((
+            MenuMarker,
+            CampaignButton,
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.),
+                    top: Val::Px(48.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba_u8(102, 45, 60, 230).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MenuMarker,
+                Localized("start-campaign"),
+                TextBundle::from_section(
+                    locale.get("start-campaign"),
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+        });
+
+    commands
+        .spawn((
+            MenuMarker,
+            AchievementsButton,
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.),
+                    top: Val::Px(70.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba_u8(102, 45, 60, 230).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MenuMarker,
+                Localized("start-achievements"),
+                TextBundle::from_section(
+                    locale.get("start-achievements"),
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+        });
+
+    if SavedRun::load().is_none() {
+        return;
+    }
+    commands
+        .spawn((
+            MenuMarker,
+            ContinueButton,
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.),
+                    top: Val::Px(92.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba_u8(102, 45, 60, 230).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MenuMarker,
+                Localized("start-continue"),
+                TextBundle::from_section(
+                    locale.get("start-continue"),
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size: 10.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+            ));
+        });
 }
 
 fn despawn_menu(mut commands: Commands, q: Query<Entity, With<MenuMarker>>) {
@@ -80,7 +327,7 @@ fn despawn_menu(mut commands: Commands, q: Query<Entity, With<MenuMarker>>) {
 }
 
 fn button_system(
-    mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
     mut state: ResMut<NextState<GameState>>,
 ) {
     for interaction in &mut interaction_query {
@@ -113,3 +360,177 @@ fn input_start(
         }
     }
 }
+
+/// Shows the seed the next run will use. Typing digits sets it, backspace clears it.
+#[derive(Component)]
+struct SeedText;
+
+impl SeedText {
+    fn update(seed: Res<RunSeed>, locale: Res<Locale>, mut q: Query<&mut Text, With<SeedText>>) {
+        for mut text in &mut q {
+            text.sections[0].value = locale.format("start-seed", &[("seed", &seed.label(&locale))]);
+        }
+    }
+}
+
+/// The best runs table.
+#[derive(Component)]
+struct ScoresText;
+
+impl ScoresText {
+    fn update(
+        scores: Res<HighScores>,
+        locale: Res<Locale>,
+        mut q: Query<&mut Text, With<ScoresText>>,
+    ) {
+        if !locale.is_changed() {
+            return;
+        }
+        for mut text in &mut q {
+            text.sections[0].value = scores.table(&locale);
+        }
+    }
+}
+
+fn type_seed(
+    mut chars: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut seed: ResMut<RunSeed>,
+) {
+    for event in chars.read() {
+        let Some(digit) = event.char.to_digit(10) else {
+            continue;
+        };
+        let typed = seed
+            .chosen
+            .unwrap_or(0)
+            .checked_mul(10)
+            .and_then(|s| s.checked_add(digit.into()));
+        if typed.is_some() {
+            seed.chosen = typed;
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        seed.chosen = seed.chosen.map(|s| s / 10).filter(|s| *s != 0);
+    }
+}
+
+/// Clicking this switches to the next difficulty preset.
+#[derive(Component)]
+struct DifficultyButton;
+
+impl DifficultyButton {
+    fn handle_clicks(
+        q: Query<&Interaction, (Changed<Interaction>, With<DifficultyButton>)>,
+        mut difficulty: ResMut<Difficulty>,
+    ) {
+        for interaction in &q {
+            if *interaction == Interaction::Pressed {
+                difficulty.preset = difficulty.preset.next();
+            }
+        }
+    }
+}
+
+/// Clicking this opens the campaign level select.
+#[derive(Component)]
+struct CampaignButton;
+
+impl CampaignButton {
+    fn handle_clicks(
+        q: Query<&Interaction, (Changed<Interaction>, With<CampaignButton>)>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        for interaction in &q {
+            if *interaction == Interaction::Pressed {
+                state.set(GameState::LevelSelect);
+            }
+        }
+    }
+}
+
+/// Clicking this opens the achievement gallery.
+#[derive(Component)]
+struct AchievementsButton;
+
+impl AchievementsButton {
+    fn handle_clicks(
+        q: Query<&Interaction, (Changed<Interaction>, With<AchievementsButton>)>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        for interaction in &q {
+            if *interaction == Interaction::Pressed {
+                state.set(GameState::Achievements);
+            }
+        }
+    }
+}
+
+/// Clicking this picks the saved run back up. Only there when a run was saved.
+#[derive(Component)]
+struct ContinueButton;
+
+impl ContinueButton {
+    fn handle_clicks(
+        q: Query<&Interaction, (Changed<Interaction>, With<ContinueButton>)>,
+        mut commands: Commands,
+        campaign: Res<Campaign>,
+        mut current_level: ResMut<CurrentLevel>,
+        mut state: ResMut<NextState<GameState>>,
+    ) {
+        for interaction in &q {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let Some(saved) = SavedRun::load() else {
+                continue;
+            };
+            match saved.level(&campaign) {
+                Ok(level) => {
+                    current_level.0 = level;
+                    commands.insert_resource(saved);
+                    state.set(GameState::Playing);
+                }
+                Err(e) => warn!("could not continue the saved run: {e}"),
+            }
+        }
+    }
+}
+
+/// Shows the chosen difficulty preset.
+#[derive(Component)]
+struct DifficultyText;
+
+impl DifficultyText {
+    fn update(
+        difficulty: Res<Difficulty>,
+        locale: Res<Locale>,
+        mut q: Query<&mut Text, With<DifficultyText>>,
+    ) {
+        for mut text in &mut q {
+            text.sections[0].value = format!("< {} >", locale.get(difficulty.preset.label()));
+        }
+    }
+}
+
+/// Left and right arrows or the gamepad d-pad step through the presets.
+fn cycle_difficulty(
+    keyboard_input: Res<Input<KeyCode>>,
+    button_inputs: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    let pressed = |key, button| {
+        keyboard_input.just_pressed(key)
+            || gamepads
+                .iter()
+                .any(|gamepad| button_inputs.just_pressed(GamepadButton::new(gamepad, button)))
+    };
+    if pressed(KeyCode::Left, GamepadButtonType::DPadLeft) {
+        difficulty.preset = difficulty.preset.previous();
+    }
+    if pressed(KeyCode::Right, GamepadButtonType::DPadRight) {
+        difficulty.preset = difficulty.preset.next();
+    }
+}
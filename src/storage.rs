@@ -0,0 +1,122 @@
+//! Data kept between launches, like high scores. Native builds write RON files
+//! to the platform data directory, wasm builds use `localStorage`.
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads what was last saved under `key`. Missing or unreadable data gives `None`.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let text = read(key)?;
+    match ron::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("ignoring saved {key}: {e}");
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let text = match ron::to_string(value) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("could not save {key}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = write(key, &text) {
+        error!("could not save {key}: {e}");
+    }
+}
+
+/// Forgets what was saved under `key`.
+pub fn remove(key: &str) {
+    if let Err(e) = delete(key) {
+        error!("could not remove saved {key}: {e}");
+    }
+}
+
+/// Seconds since the unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Seconds since the unix epoch.
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn path(key: &str) -> Option<std::path::PathBuf> {
+    let dirs = directories::ProjectDirs::from("", "", "backlog_breakdown")?;
+    Some(dirs.data_dir().join(format!("{key}.ron")))
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)?).ok()
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn write(key: &str, text: &str) -> Result<(), String> {
+    let path = path(key).ok_or("no data directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn delete(key: &str) -> Result<(), String> {
+    let path = path(key).ok_or("no data directory")?;
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+fn read(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+fn write(key: &str, text: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or("no local storage")?
+        .set_item(key, text)
+        .map_err(|e| format!("{e:?}"))
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+fn delete(key: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or("no local storage")?
+        .remove_item(key)
+        .map_err(|e| format!("{e:?}"))
+}
+
+// tests start from nothing saved and leave the player's saves alone
+#[cfg(test)]
+fn read(_key: &str) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+fn write(_key: &str, _text: &str) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+fn delete(_key: &str) -> Result<(), String> {
+    Ok(())
+}
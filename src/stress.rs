@@ -1,29 +1,35 @@
 use bevy::{ecs::system::Command, prelude::*, sprite::Anchor};
 use bevy_mod_picking::picking_core::Pickable;
 
-use crate::{game_state::GameState, layers};
+use crate::{
+    game_state::GameState,
+    layers,
+    locale::Locale,
+    queue::ConsumeActive,
+    settings::Settings,
+    sim::{Sim, SimEvent, StressCause},
+    spawning::CircleButton,
+};
 
 #[derive(Component)]
 pub struct StressMeterRect;
 
+/// Fades in as stress gets close to failing. Limits are fractions of the fail stress.
 #[derive(Component)]
 pub struct StressOverlay {
     min_visible: f32,
     max_visible: f32,
 }
 
-#[derive(Component, Default)]
-pub struct StressMeter {
-    /// 0-100
-    pub value: f32,
-}
+#[derive(Component)]
+pub struct StressMeter;
 
 impl StressMeter {
     const DIM: Vec2 = Vec2::new(16., 130.);
     pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
         commands
             .spawn((
-                StressMeter { value: 10. },
+                StressMeter,
                 SpriteBundle {
                     texture: asset_server.load("meter_stress.png"),
                     transform: Transform::from_xyz(-299., 41., layers::UI + 2.),
@@ -54,8 +60,8 @@ impl StressMeter {
 
         commands.spawn((
             StressOverlay {
-                min_visible: 50.,
-                max_visible: 100.,
+                min_visible: 0.5,
+                max_visible: 1.,
             },
             SpriteBundle {
                 sprite: Sprite {
@@ -71,8 +77,8 @@ impl StressMeter {
 
         commands.spawn((
             StressOverlay {
-                min_visible: 85.,
-                max_visible: 100.,
+                min_visible: 0.85,
+                max_visible: 1.,
             },
             SpriteBundle {
                 sprite: Sprite {
@@ -88,10 +94,10 @@ impl StressMeter {
     }
 
     pub fn animate_meter(
-        stress: Query<&StressMeter>,
+        sim: Res<Sim>,
         mut stress_rect: Query<&mut Sprite, With<StressMeterRect>>,
     ) {
-        let height = 114. * stress.single().value / 100.;
+        let height = 114. * sim.stress / sim.balance.fail_stress;
         let Some(ref mut size) = stress_rect.single_mut().custom_size else {
             return;
         };
@@ -99,14 +105,13 @@ impl StressMeter {
     }
 
     pub fn animate_stress_overlays(
-        stress: Query<&StressMeter>,
+        sim: Res<Sim>,
         mut overlays: Query<(&mut Sprite, &StressOverlay)>,
     ) {
-        let stress = stress.single();
+        let stress = sim.stress / sim.balance.fail_stress;
         for (mut sprite, overlay) in &mut overlays {
-            sprite.color.set_a(if stress.value > overlay.min_visible {
-                0.4 * (stress.value - overlay.min_visible)
-                    / (overlay.max_visible - overlay.min_visible)
+            sprite.color.set_a(if stress > overlay.min_visible {
+                0.4 * (stress - overlay.min_visible) / (overlay.max_visible - overlay.min_visible)
             } else {
                 0.
             });
@@ -114,25 +119,8 @@ impl StressMeter {
     }
 }
 
-pub struct EmitStress(pub f32);
-impl Command for EmitStress {
-    fn apply(self, world: &mut World) {
-        let mut query = world.query::<&mut StressMeter>();
-        let Ok(mut stress) = query.get_single_mut(world) else {
-            return;
-        };
-        if self.0 < 0. && stress.value <= 0. {
-            return;
-        }
-        stress.value += self.0;
-    }
-}
-
-pub fn fail_state(stress: Query<&StressMeter>, mut state: ResMut<NextState<GameState>>) {
-    let Ok(meter) = stress.get_single() else {
-        return;
-    };
-    if meter.value > 100. {
+pub fn fail_state(sim: Res<Sim>, mut state: ResMut<NextState<GameState>>) {
+    if sim.failed() {
         state.set(GameState::Failed);
     }
 }
@@ -142,9 +130,6 @@ pub struct StressText {
     timer: Timer,
 }
 
-#[derive(Component)]
-pub struct StartPosition(pub Vec3);
-
 pub struct StressPopupText {
     pub spawn_origin: Vec3,
     pub stress_value: f32,
@@ -152,9 +137,8 @@ pub struct StressPopupText {
 
 impl Command for StressPopupText {
     fn apply(self, world: &mut World) {
-        let font = world
-            .resource::<AssetServer>()
-            .load("chevyray_bird_seed.ttf");
+        let font = world.resource::<Locale>().font.clone();
+        let palette = world.resource::<Settings>().palette;
         world.spawn((
             StressText {
                 timer: Timer::from_seconds(2., TimerMode::Once),
@@ -166,28 +150,63 @@ impl Command for StressPopupText {
                         font,
                         font_size: 16.,
                         color: if self.stress_value < 0. {
-                            Color::DARK_GREEN
+                            palette.relief()
                         } else {
-                            Color::CRIMSON
+                            palette.stress()
                         },
                     },
                 ),
                 transform: Transform::from_translation(self.spawn_origin),
                 ..default()
             },
-            StartPosition(self.spawn_origin),
         ));
     }
 }
 
+impl StressPopupText {
+    /// Pops up each stress change next to whatever caused it.
+    pub fn spawn_popups(
+        mut commands: Commands,
+        mut events: EventReader<SimEvent>,
+        button: Query<&GlobalTransform, With<CircleButton>>,
+        active_slot: Query<&GlobalTransform, With<ConsumeActive>>,
+    ) {
+        for event in events.read() {
+            let SimEvent::Stress {
+                value: stress_value,
+                cause,
+            } = *event
+            else {
+                continue;
+            };
+            let spawn_origin = match cause {
+                StressCause::Buy => Vec3::new(255., 149., 200.),
+                StressCause::Consume => {
+                    active_slot.single().translation() + 33. * Vec3::Y + 101. * Vec3::Z
+                }
+                StressCause::EndOfDay => {
+                    button.single().translation() - 35. * Vec3::X + 100. * Vec3::Z
+                }
+            };
+            commands.add(StressPopupText {
+                spawn_origin,
+                stress_value,
+            });
+        }
+    }
+}
+
 impl StressText {
     pub fn animate_text(
         mut commands: Commands,
         mut texts: Query<(Entity, &mut StressText, &mut Transform, &mut Text)>,
         time: Res<Time>,
+        settings: Res<Settings>,
     ) {
         for (e, mut stress_timer, mut t, mut text) in &mut texts {
-            t.translation.y += time.delta().as_secs_f32() * 10.;
+            if !settings.reduced_motion {
+                t.translation.y += time.delta().as_secs_f32() * 10.;
+            }
             let new_a = text.sections[0].style.color.a() - 0.005 * text.sections[0].style.color.a();
             text.sections[0].style.color.set_a(new_a.max(0.0));
             if stress_timer.timer.tick(time.delta()).finished() {
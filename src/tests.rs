@@ -0,0 +1,772 @@
+//! Plays the game headlessly on stub data. The harness sends the same commands
+//! the picking callbacks do, so a click or a drop here goes down the same path
+//! as one in the game.
+
+use std::{collections::HashMap, time::Duration};
+
+use ab_glyph::FontArc;
+use bevy::{
+    ecs::system::{Command, EntityCommand},
+    gizmos::GizmoPlugin,
+    input::{keyboard::KeyboardInput, ButtonState, InputPlugin},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+};
+use bevy_rand::prelude::*;
+
+use crate::{
+    campaign::{Campaign, CurrentLevel, Level, LevelError, ScriptedAction, ScriptedCue},
+    catalog::{read_rules, CatalogCategory, CatalogItem, CatalogLoaderError, ItemCatalog},
+    curve::{CurveKey, DifficultyCurve, Keyframe},
+    difficulty::{Difficulty, Preset},
+    game_state::{GameState, PauseState},
+    high_scores::HighScores,
+    item::{ItemEntities, ItemType, ReleaseItem},
+    locale::{Locale, Locales},
+    presets::Presets,
+    queue::{AddToQueue, Queue},
+    replay::{Recorder, Recording, Replay},
+    saved_run::SaveRun,
+    seed::RunSeed,
+    settings::Settings,
+    sim::{Balance, Calendar, Goal, ItemId, Location, Sim, Variant},
+    spawning::CircleButton,
+    stack::{AddToStack, RemoveFromStack, SpawnEvent, Stack},
+    stress::StressMeterRect,
+    titles::{TitlePrinter, TitleWords},
+    GamePlugin,
+};
+
+/// Time each update advances the game by.
+const FRAME: Duration = Duration::from_millis(100);
+
+struct Harness {
+    app: App,
+}
+
+impl Harness {
+    /// Starts an endless run on normal difficulty with two categories.
+    fn new() -> Self {
+        Self::with(|_| {})
+    }
+
+    /// Starts `level`, the only one in a campaign on a catalog that has a
+    /// category between books and games, so the level's categories aren't at
+    /// their catalog indices.
+    fn level(level: Level) -> Self {
+        Self::with(|app| {
+            app.insert_resource(stub_catalog_of(&["books", "music", "games"]))
+                .insert_resource(Campaign {
+                    levels: vec![level],
+                })
+                .insert_resource(CurrentLevel(Some(0)));
+        })
+    }
+
+    /// Like [`Harness::new`], with `setup` changing the app before the run starts.
+    fn with(setup: impl FnOnce(&mut App)) -> Self {
+        let mut app = App::new();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+            .insert_resource(Settings::default())
+            .init_resource::<UiScale>()
+            .add_plugins((MinimalPlugins, AssetPlugin::default()))
+            // gizmos load their shader when they're added
+            .init_asset::<Shader>()
+            .add_plugins((
+                GizmoPlugin,
+                WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                },
+                InputPlugin,
+                EntropyPlugin::<ChaCha8Rng>::default(),
+                GamePlugin,
+            ))
+            .init_asset::<Image>()
+            .init_asset::<Font>()
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<TextureAtlas>()
+            .init_asset::<AudioSource>()
+            .insert_resource(stub_catalog())
+            .insert_resource(stub_curve())
+            .insert_resource(stub_presets())
+            .insert_resource(Campaign { levels: Vec::new() })
+            .insert_resource(Locale::default())
+            .insert_resource(Locales {
+                languages: vec![Locale::default()],
+            })
+            .insert_resource(RunSeed {
+                chosen: Some(1),
+                current: 0,
+            })
+            // the stubs are in, so skip loading and the start screen
+            .insert_resource(NextState(Some(GameState::Playing)));
+        setup(&mut app);
+
+        let mut harness = Harness { app };
+        // one update starts the run, the next spawns the item sprites
+        harness.update();
+        harness.update();
+        harness
+    }
+
+    fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Keeps updating for at least `time`.
+    fn advance(&mut self, time: Duration) {
+        let frames = (time.as_secs_f32() / FRAME.as_secs_f32()).ceil() as u32;
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
+    /// Updates until the run is over.
+    fn finish_run(&mut self) {
+        for _ in 0..10_000 {
+            self.update();
+            if self.state() == GameState::Failed {
+                return;
+            }
+        }
+        panic!("the run never ended");
+    }
+
+    /// Updates until the day ends.
+    fn finish_day(&mut self) {
+        let day = self.sim().calendar.day();
+        for _ in 0..1000 {
+            self.update();
+            if self.sim().calendar.day() > day {
+                return;
+            }
+        }
+        panic!("day {day} never ended");
+    }
+
+    fn pause_state(&self) -> PauseState {
+        self.app.world.resource::<State<PauseState>>().get().clone()
+    }
+
+    fn sim(&self) -> &Sim {
+        self.app.world.resource::<Sim>()
+    }
+
+    fn sim_mut(&mut self) -> Mut<'_, Sim> {
+        self.app.world.resource_mut::<Sim>()
+    }
+
+    fn state(&self) -> GameState {
+        self.app.world.resource::<State<GameState>>().get().clone()
+    }
+
+    /// Height of the bar in the stress meter.
+    fn meter_height(&mut self) -> f32 {
+        let world = &mut self.app.world;
+        let sprite = world
+            .query_filtered::<&Sprite, With<StressMeterRect>>()
+            .single(world);
+        sprite.custom_size.unwrap().y
+    }
+
+    /// What the buy button's click callback does.
+    fn click_buy(&mut self) {
+        let world = &mut self.app.world;
+        assert_eq!(
+            world
+                .query_filtered::<(), With<CircleButton>>()
+                .iter(world)
+                .count(),
+            1,
+            "there should be a buy button"
+        );
+        SpawnEvent.apply(world);
+    }
+
+    /// Presses and lets go of a key over two frames.
+    fn tap(&mut self, key_code: KeyCode) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world.send_event(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key_code),
+                state,
+                window: Entity::PLACEHOLDER,
+            });
+            self.update();
+        }
+    }
+
+    fn item_entity(&self, item: ItemId) -> Entity {
+        self.app.world.resource::<ItemEntities>().0[&item]
+    }
+
+    /// Picks an item up and drops it on the stack at `index`, like a drag does.
+    fn drop_on_stack(&mut self, item: ItemId, index: usize) {
+        let entity = self.item_entity(item);
+        let world = &mut self.app.world;
+        let stack = world
+            .query::<(Entity, &Stack)>()
+            .iter(world)
+            .find(|(_, stack)| stack.index == index)
+            .map(|(entity, _)| entity)
+            .expect("there is no stack with that index");
+        RemoveFromStack.apply(entity, world);
+        AddToStack(stack).apply(entity, world);
+        ReleaseItem.apply(entity, world);
+    }
+
+    /// Picks an item up and drops it on the queue, like a drag does.
+    fn drop_on_queue(&mut self, item: ItemId) {
+        let entity = self.item_entity(item);
+        let world = &mut self.app.world;
+        assert!(
+            world
+                .query_filtered::<(), With<Queue>>()
+                .iter(world)
+                .count()
+                == 1,
+            "there should be a queue"
+        );
+        RemoveFromStack.apply(entity, world);
+        AddToQueue.apply(entity, world);
+        ReleaseItem.apply(entity, world);
+    }
+
+    /// Items on the stack at `index`, bottom first.
+    fn stacked(&self, index: usize) -> Vec<ItemId> {
+        self.sim().stacks[index]
+            .items
+            .iter()
+            .map(|item| item.id)
+            .collect()
+    }
+
+    /// Whether any text on screen has this string key in it. The harness has
+    /// no strings, so text shows its keys.
+    fn screen_says(&mut self, key: &str) -> bool {
+        let world = &mut self.app.world;
+        world
+            .query::<&Text>()
+            .iter(world)
+            .flat_map(|text| &text.sections)
+            .any(|section| section.value.contains(key))
+    }
+
+    fn location(&self, item: ItemId) -> Location {
+        self.sim()
+            .items()
+            .find(|(i, _)| i.id == item)
+            .map(|(_, location)| location)
+            .expect("the item should still be around")
+    }
+}
+
+fn stub_catalog() -> ItemCatalog {
+    stub_catalog_of(&["books", "games"])
+}
+
+/// A catalog with one item in each of these categories.
+fn stub_catalog_of(ids: &[&str]) -> ItemCatalog {
+    let category = |id: &str, color| CatalogCategory {
+        id: id.to_string(),
+        label: id.to_string(),
+        color,
+        dimensions: Vec2::new(40., 20.),
+        consume_time: Duration::from_secs(2),
+        items: vec![CatalogItem {
+            side: Handle::default(),
+            cover: Handle::default(),
+            title: format!("a {id} item"),
+            height: 20.,
+            consume_time: Duration::from_secs(2),
+            weight: 1.,
+            print_title: false,
+        }],
+        generated: None,
+    };
+    let font = FontArc::try_from_slice(include_bytes!("../assets/chevyray_bird_seed.ttf"))
+        .expect("the pixel font should parse");
+    ItemCatalog {
+        categories: ids.iter().map(|id| category(id, Color::BLUE)).collect(),
+        title_words: TitleWords::default(),
+        printer: TitlePrinter::new(font),
+    }
+}
+
+/// Books and games like the first campaign level, with a goal that's quick to meet.
+fn first_week() -> Level {
+    Level {
+        id: "first-week".to_string(),
+        name: "First Week".to_string(),
+        description: String::new(),
+        categories: vec!["books".to_string(), "games".to_string()],
+        stacks: HashMap::from([("books".to_string(), 3), ("games".to_string(), 2)]),
+        script: Vec::new(),
+        goal: Goal::Consume { items: 1, days: 5 },
+        balance: None,
+    }
+}
+
+/// Every difficulty plays like the default balance.
+fn stub_presets() -> Presets {
+    Presets {
+        easy: Balance::NORMAL,
+        normal: Balance::NORMAL,
+        hard: Balance::NORMAL,
+        dialog_time: 3.5,
+    }
+}
+
+/// The same rules all run long, without bulk purchases.
+fn stub_curve() -> DifficultyCurve {
+    DifficultyCurve {
+        key: CurveKey::Elapsed,
+        keyframes: vec![Keyframe {
+            at: 0.,
+            day_length: 1.,
+            bulk_chance: 0.,
+            bulk_size: (1., 1.),
+            consume_time: 1.,
+            mismatch_penalty: 1.,
+        }],
+    }
+}
+
+#[test]
+fn run_starts_with_seeded_stacks() {
+    let harness = Harness::new();
+    assert_eq!(harness.state(), GameState::Playing);
+    let sim = harness.sim();
+    assert_eq!(sim.stress, Balance::NORMAL.start_stress);
+    assert_eq!(sim.stack_penalty, 0.);
+    assert!(sim.stacks.iter().all(|stack| !stack.items.is_empty()));
+}
+
+#[test]
+fn unbought_day_adds_missed_buy_and_stack_penalties() {
+    let mut harness = Harness::new();
+    let misplaced = harness.stacked(0)[0];
+    harness.drop_on_stack(misplaced, 1);
+    harness.update();
+    assert_eq!(harness.location(misplaced), Location::Stack(1));
+    let stack_penalty = harness.sim().stack_penalty;
+    assert_eq!(stack_penalty, Balance::NORMAL.mismatch_penalty);
+
+    let stress = harness.sim().stress;
+    harness.finish_day();
+    let expected = stress + Balance::NORMAL.missed_buy_penalty + stack_penalty;
+    assert_eq!(harness.sim().stress, expected);
+    assert!(!harness.sim().calendar.days[0].bought);
+
+    harness.update();
+    let meter = 114. * expected / Balance::NORMAL.fail_stress;
+    assert!((harness.meter_height() - meter).abs() < 0.01);
+}
+
+#[test]
+fn bought_day_adds_bought_penalty() {
+    let mut harness = Harness::new();
+    let items = harness.sim().items().count();
+    harness.click_buy();
+    harness.update();
+    assert_eq!(harness.sim().items().count(), items + 1);
+    assert!(harness.sim().today.clicked_today);
+
+    // the buy takes a point off right away
+    let stress = harness.sim().stress;
+    assert_eq!(stress, Balance::NORMAL.start_stress - 1.);
+    harness.finish_day();
+    let penalty = Balance::NORMAL.bought_penalty + harness.sim().stack_penalty;
+    assert_eq!(harness.sim().stress, stress + penalty);
+}
+
+#[test]
+fn full_queue_rejects_third_item() {
+    let mut harness = Harness::new();
+    let books = harness.stacked(0);
+    let games = harness.stacked(1);
+    let picked = [books[0], books[1], games[0]];
+    for item in picked {
+        harness.drop_on_queue(item);
+    }
+    harness.update();
+
+    // the first goes straight to the consume slot, the second waits
+    assert_eq!(harness.location(picked[0]), Location::Active);
+    assert_eq!(harness.location(picked[1]), Location::Queue(0));
+    assert!(matches!(harness.location(picked[2]), Location::Stack(_)));
+}
+
+#[test]
+fn consuming_an_item_counts_it_and_relieves_stress() {
+    let mut harness = Harness::new();
+    let item = harness.stacked(1)[0];
+    harness.drop_on_queue(item);
+    harness.update();
+    assert_eq!(harness.location(item), Location::Active);
+
+    let stress = harness.sim().stress;
+    harness.advance(Duration::from_secs(2));
+    let sim = harness.sim();
+    assert!(sim.items().all(|(i, _)| i.id != item));
+    assert_eq!(sim.consumed.total, 1);
+    assert_eq!(sim.consumed.by_type.len(), 1);
+    assert_eq!(sim.stress, stress - 1.);
+}
+
+#[test]
+fn stress_over_the_limit_fails_the_run() {
+    let mut harness = Harness::new();
+    harness.sim_mut().stress = Balance::NORMAL.fail_stress + 1.;
+    harness.update();
+    harness.update();
+    assert_eq!(harness.state(), GameState::Failed);
+}
+
+#[test]
+fn buying_during_a_sale_brings_several_items_of_its_category() {
+    let mut harness = Harness::with(|app| {
+        let normal = Balance {
+            sale_chance: 1,
+            ..Balance::NORMAL
+        };
+        app.insert_resource(Presets {
+            normal,
+            ..stub_presets()
+        });
+    });
+    harness.click_buy();
+    harness.finish_day();
+    let sale = harness
+        .sim()
+        .sale
+        .expect("every day should announce a sale");
+    assert!(!sale.active());
+    harness.advance(sale.starts_in);
+    assert!(harness.sim().sale.is_some_and(|sale| sale.active()));
+
+    let on_sale = |harness: &Harness| {
+        let items = harness.sim().items();
+        items
+            .filter(|(item, _)| item.item_type == sale.item_type)
+            .count()
+    };
+    let (before, stress) = (on_sale(&harness), harness.sim().stress);
+    harness.click_buy();
+    harness.update();
+    let bought = on_sale(&harness) - before;
+    assert!(bought >= Balance::NORMAL.min_sale_items);
+    assert!(bought <= Balance::NORMAL.max_sale_items);
+    assert_eq!(harness.sim().stress, stress - Balance::NORMAL.sale_relief);
+}
+
+#[test]
+fn week_summary_opens_when_the_week_ends() {
+    let mut harness = Harness::new();
+    for _ in 1..Calendar::DAYS_PER_WEEK {
+        harness.click_buy();
+        harness.finish_day();
+    }
+    harness.update();
+    assert_eq!(harness.pause_state(), PauseState::Running);
+    harness.click_buy();
+    harness.finish_day();
+    harness.update();
+    assert_eq!(harness.pause_state(), PauseState::WeekSummary);
+    let days = &harness.sim().calendar.days;
+    assert_eq!(days.len(), Calendar::DAYS_PER_WEEK);
+    assert!(days.iter().all(|day| day.items_bought > 0));
+}
+
+#[test]
+fn difficulty_curve_blends_between_keyframes() {
+    let start = stub_curve().keyframes[0];
+    let curve = DifficultyCurve {
+        key: CurveKey::Elapsed,
+        keyframes: vec![
+            Keyframe { at: 10., ..start },
+            Keyframe {
+                at: 20.,
+                day_length: 0.5,
+                consume_time: 2.,
+                ..start
+            },
+        ],
+    };
+    assert_eq!(curve.sample(0.).day_length, 1.);
+    assert_eq!(curve.sample(15.).day_length, 0.75);
+    assert_eq!(curve.sample(15.).consume_time, 1.5);
+    assert_eq!(curve.sample(30.).consume_time, 2.);
+
+    // a run samples the curve by how long it's been going
+    let mut harness = Harness::with(|app| {
+        app.insert_resource(curve);
+    });
+    harness.advance(Duration::from_secs(15));
+    let day_length = harness.sim().pressure().day_length;
+    assert!((0.74..0.76).contains(&day_length));
+}
+
+#[test]
+fn resumed_run_picks_up_where_it_was_saved() {
+    let mut harness = Harness::new();
+    let item = harness.stacked(1)[0];
+    harness.drop_on_queue(item);
+    harness.click_buy();
+    harness.finish_day();
+    harness.advance(Duration::from_secs(1));
+    let saved = ron::to_string(&harness.sim().state()).expect("the run should save");
+
+    let mut resumed = Harness::new();
+    resumed
+        .sim_mut()
+        .resume(ron::from_str(&saved).expect("the save should load"))
+        .expect("the save should fit the run");
+    resumed.update();
+    let (before, after) = (harness.sim(), resumed.sim());
+    assert_eq!(after.stress, before.stress);
+    assert_eq!(after.calendar.days, before.calendar.days);
+    assert_eq!(after.consumed, before.consumed);
+    assert_eq!(after.elapsed, before.elapsed + FRAME);
+    assert!(after.today.fraction() > before.today.fraction());
+    for index in 0..2 {
+        assert_eq!(resumed.stacked(index), harness.stacked(index));
+    }
+    // the resumed run's sprites follow the saved stacks
+    resumed.update();
+    for index in 0..2 {
+        for item in resumed.stacked(index) {
+            resumed.item_entity(item);
+        }
+    }
+}
+
+#[test]
+fn resumed_level_run_keeps_its_categories() {
+    let mut harness = Harness::level(first_week());
+    harness.advance(Duration::from_secs(1));
+    // games is the level's second category but the catalog's third
+    assert_eq!(harness.sim().stacks[1].item_type, ItemType(2));
+    assert!(!harness.stacked(1).is_empty());
+    let saved = ron::to_string(&harness.sim().state()).expect("the run should save");
+
+    let mut resumed = Harness::level(first_week());
+    resumed
+        .sim_mut()
+        .resume(ron::from_str(&saved).expect("the save should load"))
+        .expect("the save should fit the level");
+    for index in 0..2 {
+        assert_eq!(resumed.stacked(index), harness.stacked(index));
+    }
+}
+
+#[test]
+fn impossible_cue_times_turn_the_level_down() {
+    let catalog = stub_catalog_of(&["books", "music", "games"]);
+    for at in [-1., f32::NAN, f32::INFINITY] {
+        let level = Level {
+            script: vec![ScriptedCue {
+                at,
+                action: ScriptedAction::Dialog("Welcome!".to_string()),
+            }],
+            ..first_week()
+        };
+        assert!(matches!(
+            level.rules(&catalog),
+            Err(LevelError::BadCueTime { level, .. }) if level == "first-week"
+        ));
+    }
+}
+
+#[test]
+fn resumed_level_run_is_won_by_its_goal() {
+    let mut harness = Harness::level(first_week());
+    let item = harness.stacked(0)[0];
+    harness.drop_on_queue(item);
+    harness.update();
+    assert_eq!(harness.location(item), Location::Active);
+    let saved = ron::to_string(&harness.sim().state()).expect("the run should save");
+
+    let mut resumed = Harness::level(first_week());
+    resumed
+        .sim_mut()
+        .resume(ron::from_str(&saved).expect("the save should load"))
+        .expect("the save should fit the level");
+    resumed.update();
+    assert_eq!(resumed.location(item), Location::Active);
+    assert_eq!(resumed.state(), GameState::Playing);
+    resumed.advance(Duration::from_secs(3));
+    assert_eq!(resumed.sim().consumed.total, 1);
+    assert_eq!(resumed.state(), GameState::Victory);
+}
+
+#[test]
+fn saving_leaves_the_rng_alone() {
+    let mut harness = Harness::new();
+    harness.advance(Duration::from_secs(1));
+    let rng = harness
+        .app
+        .world
+        .resource::<GlobalEntropy<ChaCha8Rng>>()
+        .clone();
+    SaveRun.apply(&mut harness.app.world);
+    assert_eq!(
+        *harness.app.world.resource::<GlobalEntropy<ChaCha8Rng>>(),
+        rng
+    );
+}
+
+#[test]
+fn saves_that_dont_fit_the_run_are_turned_down() {
+    let mut harness = Harness::new();
+    let item = harness.stacked(0)[0];
+    let stress = harness.sim().stress;
+    let broken: [fn(&mut Sim); 4] = [
+        |sim| sim.stacks[0].items[0].variant = Variant::Catalog(99),
+        |sim| {
+            sim.stacks[0].items[0].variant = Variant::Generated { blank: 0, title: 0 };
+        },
+        |sim| sim.stacks[0].items[0].offset = f32::NAN,
+        |sim| sim.stacks[1].items[0].id = ItemId(u64::MAX),
+    ];
+    for breaks in broken {
+        let mut saved = Harness::new();
+        saved.sim_mut().stress = 50.;
+        breaks(&mut saved.sim_mut());
+        let state = saved.sim().state();
+        assert!(harness.sim_mut().resume(state).is_err());
+        assert_eq!(harness.sim().stress, stress);
+        assert_eq!(harness.stacked(0)[0], item);
+    }
+}
+
+#[test]
+fn impossible_custom_difficulty_plays_on_normal() {
+    let presets = stub_presets();
+    let difficulty = Difficulty {
+        preset: Preset::Custom,
+        custom: Balance {
+            day_length: f32::INFINITY,
+            ..Balance::NORMAL
+        },
+    };
+    assert_eq!(difficulty.balance(&presets), presets.normal);
+}
+
+#[test]
+fn replayed_run_ends_like_the_recorded_one() {
+    let path = std::env::temp_dir().join(format!("replay-test-{}.ron", std::process::id()));
+    let short = Presets {
+        normal: Balance {
+            fail_stress: 20.,
+            ..Balance::NORMAL
+        },
+        ..stub_presets()
+    };
+    let mut recorded = Harness::with(|app| {
+        app.insert_resource(short)
+            .insert_resource(Recorder::new(path.clone()));
+    });
+    // queue the top item of the first stack with the cursor, then buy
+    for key in [
+        KeyCode::Space,
+        KeyCode::Down,
+        KeyCode::Space,
+        KeyCode::Up,
+        KeyCode::Right,
+        KeyCode::Right,
+        KeyCode::Space,
+    ] {
+        recorded.tap(key);
+    }
+    recorded.finish_run();
+    assert_eq!(recorded.sim().consumed.total, 1);
+
+    let recording = Recording::read(path.to_str().unwrap());
+    let _ = std::fs::remove_file(&path);
+    let mut recording = recording.expect("the run should be recorded");
+    let expected = recording.result.take().expect("the run should have ended");
+    // the harness's frames all take as long, past the first one
+    assert_eq!(recording.frames.len(), 2);
+    assert_eq!(expected.consumed, recorded.sim().consumed);
+
+    let mut replayed = Harness::with(|app| {
+        app.insert_resource(Replay::new(recording));
+    });
+    replayed.finish_run();
+    assert_eq!(replayed.sim().consumed, expected.consumed);
+    assert_eq!(replayed.sim().elapsed, expected.elapsed);
+}
+
+#[test]
+fn catalog_rules_are_checked_like_the_loaded_catalog() {
+    let category = |id: &str, print_title: bool| {
+        format!(
+            "(id: {id:?}, label: {id:?}, color: (0, 0, 0), dimensions: (10., 20.), \
+             consume_time: 1., items: [(side: \"side.png\", cover: \"cover.png\", \
+             title: \"A\", print_title: {print_title})])"
+        )
+    };
+    let read = |categories: &[String]| {
+        read_rules(format!("(categories: [{}])", categories.join(", ")).as_bytes())
+    };
+    let books = category("books", false);
+    assert!(matches!(read(&[]), Err(CatalogLoaderError::NoCategories)));
+    assert!(matches!(
+        read(&[books.clone(), books.clone()]),
+        Err(CatalogLoaderError::DuplicateCategory(id)) if id == "books"
+    ));
+    assert!(matches!(
+        read(&[category("games", true)]),
+        Err(CatalogLoaderError::NoTitleAreas(id)) if id == "games"
+    ));
+    for (good, bad) in [
+        ("dimensions: (10., 20.)", "dimensions: (10., -20.)"),
+        ("dimensions: (10., 20.)", "dimensions: (inf, 20.)"),
+        ("consume_time: 1.", "consume_time: 0."),
+        ("consume_time: 1.", "consume_time: NaN"),
+    ] {
+        assert!(matches!(
+            read(&[books.replace(good, bad)]),
+            Err(CatalogLoaderError::BadSize(id)) if id == "books"
+        ));
+    }
+    for bad in ["height: Some(-1.)", "consume_time: Some(inf)"] {
+        assert!(matches!(
+            read(&[books.replace("title: \"A\"", &format!("title: \"A\", {bad}"))]),
+            Err(CatalogLoaderError::BadItem { title, .. }) if title == "A"
+        ));
+    }
+    for bad in ["weight: -1.", "weight: 0."] {
+        assert!(matches!(
+            read(&[books.replace("title: \"A\"", &format!("title: \"A\", {bad}"))]),
+            Err(CatalogLoaderError::BadWeights(id)) if id == "books"
+        ));
+    }
+    assert_eq!(read(&[books]).expect("the catalog should read").len(), 1);
+}
+
+#[test]
+fn only_lost_runs_make_the_high_scores() {
+    let mut ended = Harness::new();
+    // what the pause menu's end run button does
+    ended
+        .app
+        .world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Failed);
+    ended.update();
+    assert_eq!(ended.state(), GameState::Failed);
+    assert!(ended.app.world.resource::<HighScores>().runs.is_empty());
+    assert!(ended.screen_says("fail-ended"));
+
+    let mut lost = Harness::new();
+    lost.sim_mut().stress = Balance::NORMAL.fail_stress + 1.;
+    lost.finish_run();
+    assert_eq!(lost.app.world.resource::<HighScores>().runs.len(), 1);
+    assert!(lost.screen_says("fail-rank"));
+}
@@ -0,0 +1,335 @@
+//! Items with generated titles. A title is made up from word lists in the
+//! catalog and printed onto blank cover and spine art with the game font.
+
+use ab_glyph::{point, Font as _, FontArc, PxScale, ScaleFont};
+use bevy::{prelude::*, render::render_resource::TextureFormat, utils::HashMap};
+use bevy_rand::prelude::ChaCha8Rng;
+use rand_core::SeedableRng;
+use serde::Deserialize;
+
+use crate::{
+    catalog::ItemCatalog,
+    item::ItemType,
+    sim::{random_index, Sim, SimEvent, Variant},
+};
+
+/// Makes titles out of patterns like `"The {adjective} {noun}"`, where each
+/// `{name}` is replaced with a word from the list of that name.
+#[derive(Deserialize, Clone, Default)]
+pub struct TitleWords {
+    pub patterns: Vec<String>,
+    pub words: HashMap<String, Vec<String>>,
+}
+
+impl TitleWords {
+    /// The same seed always makes the same title.
+    pub fn title(&self, seed: u64) -> String {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        if self.patterns.is_empty() {
+            return String::new();
+        }
+        let pattern = &self.patterns[random_index(&mut rng, self.patterns.len())];
+
+        let mut title = String::new();
+        let mut rest = pattern.as_str();
+        while let Some(start) = rest.find('{') {
+            title.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 1..start + end];
+            if let Some(words) = self.words.get(name).filter(|w| !w.is_empty()) {
+                title.push_str(&words[random_index(&mut rng, words.len())]);
+            }
+            rest = &rest[start + end + 1..];
+        }
+        title.push_str(rest);
+        title
+    }
+
+    /// Pattern placeholders without a word list.
+    pub fn missing_lists(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        for pattern in &self.patterns {
+            let mut rest = pattern.as_str();
+            while let Some(start) = rest.find('{') {
+                let Some(end) = rest[start..].find('}') else {
+                    break;
+                };
+                let name = &rest[start + 1..start + end];
+                if !self.words.contains_key(name) && !missing.iter().any(|m| m == name) {
+                    missing.push(name.to_string());
+                }
+                rest = &rest[start + end + 1..];
+            }
+        }
+        missing
+    }
+}
+
+/// Pixel rectangle of the art a title is printed in.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct TitleArea {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Prints titles onto art with the game font.
+#[derive(Clone)]
+pub struct TitlePrinter {
+    font: FontArc,
+}
+
+impl TitlePrinter {
+    /// Sizes the pixel font stays crisp at, largest first.
+    const SIZES: [f32; 2] = [20., 10.];
+    /// Pixels between lines.
+    const LINE_GAP: f32 = 1.;
+
+    pub fn new(font: FontArc) -> Self {
+        Self { font }
+    }
+
+    /// Blank art must be 8-bit RGBA so it can be drawn on.
+    pub fn can_print_on(image: &Image) -> bool {
+        matches!(
+            image.texture_descriptor.format,
+            TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+        )
+    }
+
+    /// A copy of `blank` with `title` centered in `area`, as large as fits
+    /// and wrapped over several lines if needed.
+    pub fn print(&self, blank: &Image, area: TitleArea, title: &str) -> Image {
+        let mut image = blank.clone();
+        let width = image.texture_descriptor.size.width;
+        let height = image.texture_descriptor.size.height;
+        let area = TitleArea {
+            width: area.width.min(width.saturating_sub(area.x)),
+            height: area.height.min(height.saturating_sub(area.y)),
+            ..area
+        };
+        let title = title.to_uppercase();
+        let (scale, lines) = self.fit(&title, area);
+        let color = text_color(&image, area);
+
+        let font = self.font.as_scaled(scale);
+        let line_height = self.cap_height(scale);
+        let text_height = self.text_height(scale, lines.len());
+        let mut baseline =
+            area.y as f32 + ((area.height as f32 - text_height) / 2.).max(0.).floor() + line_height;
+        for line in lines {
+            let mut x = area.x as f32
+                + ((area.width as f32 - self.width(&line, scale)) / 2.)
+                    .max(0.)
+                    .floor();
+            let mut previous = None;
+            for c in line.chars() {
+                let id = font.glyph_id(c);
+                if let Some(previous) = previous {
+                    x += font.kern(previous, id);
+                }
+                previous = Some(id);
+                let glyph = id.with_scale_and_position(scale, point(x.round(), baseline));
+                x += font.h_advance(id);
+                let Some(outline) = self.font.outline_glyph(glyph) else {
+                    continue;
+                };
+                let bounds = outline.px_bounds();
+                outline.draw(|gx, gy, coverage| {
+                    // hard edges to match the pixel art
+                    if coverage < 0.5 {
+                        return;
+                    }
+                    let px = bounds.min.x as i32 + gx as i32;
+                    let py = bounds.min.y as i32 + gy as i32;
+                    if px < area.x as i32
+                        || py < area.y as i32
+                        || px >= (area.x + area.width) as i32
+                        || py >= (area.y + area.height) as i32
+                    {
+                        return;
+                    }
+                    let i = (py as usize * width as usize + px as usize) * 4;
+                    image.data[i..i + 3].copy_from_slice(&color);
+                });
+            }
+            baseline += line_height + Self::LINE_GAP;
+        }
+        image
+    }
+
+    /// Titles are printed in capitals, so lines only need to be this tall.
+    fn cap_height(&self, scale: PxScale) -> f32 {
+        let font = self.font.as_scaled(scale);
+        let cap = font.glyph_id('H');
+        self.font
+            .outline_glyph(cap.with_scale(scale))
+            .map_or(font.ascent(), |outline| outline.px_bounds().height())
+            .round()
+    }
+
+    fn text_height(&self, scale: PxScale, lines: usize) -> f32 {
+        lines as f32 * (self.cap_height(scale) + Self::LINE_GAP) - Self::LINE_GAP
+    }
+
+    fn width(&self, text: &str, scale: PxScale) -> f32 {
+        let font = self.font.as_scaled(scale);
+        let mut width = 0.;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                width += font.kern(previous, id);
+            }
+            width += font.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    }
+
+    /// Finds the largest size the title fits at. Titles that don't fit even at
+    /// the smallest size are cut short.
+    fn fit(&self, title: &str, area: TitleArea) -> (PxScale, Vec<String>) {
+        for size in Self::SIZES {
+            let scale = PxScale::from(size);
+            let lines = self.wrap(title, scale, area.width as f32);
+            if self.text_height(scale, lines.len()) <= area.height as f32 {
+                return (scale, lines);
+            }
+        }
+        let scale = PxScale::from(Self::SIZES[Self::SIZES.len() - 1]);
+        let mut lines = self.wrap(title, scale, area.width as f32);
+        while lines.len() > 1 && self.text_height(scale, lines.len()) > area.height as f32 {
+            lines.pop();
+        }
+        (scale, lines)
+    }
+
+    fn wrap(&self, text: &str, scale: PxScale, width: f32) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for word in text.split_whitespace() {
+            if let Some(line) = lines.last_mut() {
+                let joined = format!("{line} {word}");
+                if self.width(&joined, scale) <= width {
+                    *line = joined;
+                    continue;
+                }
+            }
+            let mut word = word.to_string();
+            while self.width(&word, scale) > width && word.len() > 1 {
+                word.pop();
+            }
+            lines.push(word);
+        }
+        lines
+    }
+}
+
+/// Dark text on light art and light text on dark art.
+fn text_color(image: &Image, area: TitleArea) -> [u8; 3] {
+    let width = image.texture_descriptor.size.width as usize;
+    let mut total = 0.;
+    let mut count = 0.;
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let i = (y as usize * width + x as usize) * 4;
+            let [r, g, b] = [image.data[i], image.data[i + 1], image.data[i + 2]];
+            total += 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            count += 1.;
+        }
+    }
+    if count > 0. && total / count > 140. {
+        [40, 30, 35]
+    } else {
+        [238, 232, 225]
+    }
+}
+
+/// Side and cover art and the title of one item.
+#[derive(Clone)]
+pub struct ItemLook {
+    pub side: Handle<Image>,
+    pub cover: Handle<Image>,
+    pub title: String,
+}
+
+/// Art for the items in the run. Titles are printed the first time an item is
+/// needed and forgotten once it's consumed.
+#[derive(Resource, Default)]
+pub struct ItemArt(HashMap<(ItemType, Variant), ItemLook>);
+
+impl ItemArt {
+    pub fn look(
+        &mut self,
+        catalog: &ItemCatalog,
+        images: &mut Assets<Image>,
+        item_type: ItemType,
+        variant: Variant,
+    ) -> &ItemLook {
+        self.0.entry((item_type, variant)).or_insert_with(|| {
+            let category = catalog.category(item_type);
+            let (side, cover, title) = match variant {
+                Variant::Catalog(index) => {
+                    let item = &category.items[index];
+                    let look = ItemLook {
+                        side: item.side.clone(),
+                        cover: item.cover.clone(),
+                        title: item.title.clone(),
+                    };
+                    if !item.print_title {
+                        return look;
+                    }
+                    (look.side, look.cover, look.title)
+                }
+                Variant::Generated { blank, title } => {
+                    let blank = &category.generated.as_ref().unwrap().blanks[blank];
+                    (
+                        blank.side.clone(),
+                        blank.cover.clone(),
+                        catalog.title_words.title(title),
+                    )
+                }
+            };
+            // the catalog only allows printed titles with title areas
+            let generated = category.generated.as_ref().unwrap();
+            let mut print = |blank: Handle<Image>, area| match images.get(&blank) {
+                Some(image) => {
+                    let printed = catalog.printer.print(image, area, &title);
+                    images.add(printed)
+                }
+                None => blank,
+            };
+            ItemLook {
+                side: print(side, generated.side_area),
+                cover: print(cover, generated.cover_area),
+                title,
+            }
+        })
+    }
+
+    /// Drops the art of consumed items unless another item in the run looks
+    /// the same. Printed titles are freed once their sprites are gone too.
+    pub fn forget_consumed(
+        mut art: ResMut<ItemArt>,
+        mut events: EventReader<SimEvent>,
+        sim: Res<Sim>,
+    ) {
+        for event in events.read() {
+            let SimEvent::Consumed {
+                item_type, variant, ..
+            } = *event
+            else {
+                continue;
+            };
+            let in_play = sim
+                .items()
+                .any(|(item, _)| item.item_type == item_type && item.variant == variant);
+            if !in_play {
+                art.0.remove(&(item_type, variant));
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    game_state::{GameState, PauseState},
+    item::{ItemEntities, ReleaseItem},
+    layers,
+    queue::{AddToQueue, Queue},
+    sim::{ItemId, PlayerInput, Sim},
+    stack::{AddToStack, RemoveFromStack, Stack},
+};
+
+/// Drags items with touches. Holding a finger on an item picks it up, and
+/// lifting it drops the item on the closest stack or the queue, so a drop
+/// doesn't have to land exactly on its target under the finger. Every finger
+/// drags on its own.
+///
+/// Mouse drags go through the picking handlers on the items, which leave
+/// touches to this.
+pub struct TouchPlugin;
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchDrags>()
+            .add_systems(OnEnter(GameState::Playing), TouchDrags::reset)
+            .add_systems(
+                Update,
+                TouchDrags::track
+                    .in_set(PlayerInput)
+                    .run_if(in_state(GameState::Playing).and_then(in_state(PauseState::Running))),
+            );
+    }
+}
+
+/// Whether a picking event came from a touch rather than the mouse.
+pub fn is_touch(pointer: PointerId) -> bool {
+    matches!(pointer, PointerId::Touch(_))
+}
+
+/// Fingers down on the play area, by touch id.
+#[derive(Resource, Default)]
+pub struct TouchDrags(HashMap<u64, TouchDrag>);
+
+struct TouchDrag {
+    /// Where the finger went down, in world space.
+    start: Vec2,
+    held_for: Duration,
+    /// Item under the finger when it went down.
+    item: Option<ItemId>,
+    /// The long press went through and the item is off its stack.
+    picked: bool,
+    /// The finger moved before the long press, so it won't pick anything up.
+    moved: bool,
+}
+
+impl TouchDrags {
+    const LONG_PRESS: Duration = Duration::from_millis(300);
+    /// How far a finger can wander while long pressing.
+    const SLOP: f32 = 8.;
+    /// Drops further than this from every target go back to a stack.
+    const SNAP_DISTANCE: f32 = 80.;
+
+    fn reset(mut drags: ResMut<TouchDrags>) {
+        drags.0.clear();
+    }
+
+    fn track(
+        mut commands: Commands,
+        touches: Res<Touches>,
+        time: Res<Time>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+        sim: Res<Sim>,
+        entities: Res<ItemEntities>,
+        stacks: Query<(Entity, &Stack, &Transform, &Sprite)>,
+        queue: Query<&Transform, With<Queue>>,
+        mut items: Query<&mut Transform, (With<ItemId>, Without<Stack>, Without<Queue>)>,
+        mut drags: ResMut<TouchDrags>,
+    ) {
+        let Ok((camera, camera_transform)) = cameras.get_single() else {
+            return;
+        };
+        let to_world = |position| camera.viewport_to_world_2d(camera_transform, position);
+
+        for touch in touches.iter_just_pressed() {
+            let Some(start) = to_world(touch.position()) else {
+                continue;
+            };
+            drags.0.insert(
+                touch.id(),
+                TouchDrag {
+                    start,
+                    held_for: Duration::ZERO,
+                    item: item_at(&sim, &stacks, start),
+                    picked: false,
+                    moved: false,
+                },
+            );
+        }
+
+        for touch in touches.iter() {
+            let (Some(drag), Some(position)) =
+                (drags.0.get_mut(&touch.id()), to_world(touch.position()))
+            else {
+                continue;
+            };
+            let Some(e) = drag.item.and_then(|id| entities.0.get(&id)) else {
+                continue;
+            };
+            if drag.picked {
+                if let Ok(mut transform) = items.get_mut(*e) {
+                    transform.translation = position.extend(layers::ITEMS + 1.);
+                }
+                continue;
+            }
+
+            drag.held_for += time.delta();
+            drag.moved |= position.distance(drag.start) > Self::SLOP;
+            let still_stacked = sim
+                .stacks
+                .iter()
+                .any(|stack| stack.items.iter().any(|item| Some(item.id) == drag.item));
+            if !drag.moved && drag.held_for >= Self::LONG_PRESS && still_stacked {
+                commands
+                    .entity(*e)
+                    .insert(Pickable::IGNORE)
+                    .add(RemoveFromStack);
+                drag.picked = true;
+            }
+        }
+
+        for touch in touches.iter_just_released() {
+            let Some(drag) = drags.0.remove(&touch.id()) else {
+                continue;
+            };
+            let (true, Some(e)) = (drag.picked, drag.item.and_then(|id| entities.0.get(&id)))
+            else {
+                continue;
+            };
+            let mut item = commands.entity(*e);
+            let target = to_world(touch.position())
+                .and_then(|position| nearest_target(&stacks, &queue, position));
+            match target {
+                Some(Target::Stack(stack)) => {
+                    item.add(AddToStack(stack));
+                }
+                Some(Target::Queue) => {
+                    item.add(AddToQueue);
+                }
+                None => {}
+            }
+            item.add(ReleaseItem);
+        }
+
+        for touch in touches.iter_just_canceled() {
+            let Some(drag) = drags.0.remove(&touch.id()) else {
+                continue;
+            };
+            if let (true, Some(e)) = (drag.picked, drag.item.and_then(|id| entities.0.get(&id))) {
+                commands.entity(*e).add(ReleaseItem);
+            }
+        }
+    }
+}
+
+enum Target {
+    Stack(Entity),
+    Queue,
+}
+
+/// The stacked item at `position`, from the item heights the sim stacks by.
+fn item_at(
+    sim: &Sim,
+    stacks: &Query<(Entity, &Stack, &Transform, &Sprite)>,
+    position: Vec2,
+) -> Option<ItemId> {
+    for (_, stack, transform, sprite) in stacks {
+        let width = sprite.custom_size.map_or(0., |size| size.x);
+        if (position.x - transform.translation.x).abs() > width / 2. {
+            continue;
+        }
+        let mut bottom = transform.translation.y;
+        for item in &sim.stacks.get(stack.index)?.items {
+            if (bottom..bottom + item.height).contains(&position.y) {
+                return Some(item.id);
+            }
+            bottom += item.height;
+        }
+    }
+    None
+}
+
+/// The stack or queue closest to `position`, if any is close enough.
+fn nearest_target(
+    stacks: &Query<(Entity, &Stack, &Transform, &Sprite)>,
+    queue: &Query<&Transform, With<Queue>>,
+    position: Vec2,
+) -> Option<Target> {
+    let distance = |center: Vec2, size: Vec2| {
+        ((position - center).abs() - size / 2.)
+            .max(Vec2::ZERO)
+            .length()
+    };
+    let stacks = stacks.iter().filter_map(|(e, _, transform, sprite)| {
+        let size = sprite.custom_size?;
+        let center = transform.translation.xy() + Vec2::Y * size.y / 2.;
+        Some((Target::Stack(e), distance(center, size)))
+    });
+    let queue = queue.get_single().ok().map(|transform| {
+        (
+            Target::Queue,
+            distance(transform.translation.xy(), Queue::SIZE),
+        )
+    });
+
+    stacks
+        .chain(queue)
+        .filter(|(_, distance)| *distance <= TouchDrags::SNAP_DISTANCE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(target, _)| target)
+}